@@ -8,6 +8,20 @@ import { getVersions } from "./versions";
 
 logger.prefix = "build-script";
 
+// Cargo packages built for every native target: the CLI binary and the Node.js addon
+const NATIVE_PACKAGES = ["-p", "nexp-compiler-rs", "-p", "nexp-compiler-napi"];
+
+// Copy a built addon to `native/<targetDir>/nexp-compiler.node`,
+// this path must match the path in `src/build.ts > findCompilerAddon()` call
+function copyNapiAddon(libPath: string, targetDir: string) {
+  const addonPath = path.resolve("native", targetDir, "nexp-compiler.node");
+  if (!existsSync(path.dirname(addonPath))) {
+    mkdirSync(path.dirname(addonPath), { recursive: true });
+  }
+  copyFileSync(libPath, addonPath);
+  logger.info(`${libPath} copied to ${addonPath}`);
+}
+
 async function build_rust_x86_64_unknown_linux_gnu() {
   let cmdName: string;
   if (platform() === "linux" && arch() === "x64") {
@@ -19,7 +33,13 @@ async function build_rust_x86_64_unknown_linux_gnu() {
 
   await spawnAsync(
     cmdName,
-    ["build", "--release", "--target", "x86_64-unknown-linux-gnu"],
+    [
+      "build",
+      "--release",
+      "--target",
+      "x86_64-unknown-linux-gnu",
+      ...NATIVE_PACKAGES,
+    ],
     "rust-x86_64-unknown-linux-gnu",
     {
       cwd: path.resolve("src-rust"),
@@ -43,6 +63,17 @@ async function build_rust_x86_64_unknown_linux_gnu() {
   copyFileSync(binaryPath, distPath);
   logger.info(`${binaryPath} copied to ${distPath}`);
 
+  copyNapiAddon(
+    path.resolve(
+      "src-rust",
+      "target",
+      "x86_64-unknown-linux-gnu",
+      "release",
+      "libnexp_compiler_napi.so",
+    ),
+    "x86_64-unknown-linux-gnu",
+  );
+
   logger.info("Build rust-x86_64-unknown-linux-gnu DONE");
 }
 
@@ -57,7 +88,13 @@ async function build_rust_x86_64_pc_windows_gnu() {
 
   await spawnAsync(
     cmdName,
-    ["build", "--release", "--target", "x86_64-pc-windows-gnu"],
+    [
+      "build",
+      "--release",
+      "--target",
+      "x86_64-pc-windows-gnu",
+      ...NATIVE_PACKAGES,
+    ],
     "rust-x86_64-pc-windows-gnu",
     {
       cwd: path.resolve("src-rust"),
@@ -81,6 +118,17 @@ async function build_rust_x86_64_pc_windows_gnu() {
   copyFileSync(binaryPath, distPath);
   logger.info(`${binaryPath} copied to ${distPath}`);
 
+  copyNapiAddon(
+    path.resolve(
+      "src-rust",
+      "target",
+      "x86_64-pc-windows-gnu",
+      "release",
+      "nexp_compiler_napi.dll",
+    ),
+    "x86_64-pc-windows-gnu",
+  );
+
   logger.info("Build rust-x86_64-pc-windows-gnu DONE");
 }
 
@@ -92,7 +140,13 @@ async function build_rust_x86_64_apple_darwin() {
   }
   await spawnAsync(
     "cargo",
-    ["build", "--release", "--target", "x86_64-apple-darwin"],
+    [
+      "build",
+      "--release",
+      "--target",
+      "x86_64-apple-darwin",
+      ...NATIVE_PACKAGES,
+    ],
     "rust-x86_64-apple-darwin",
     {
       cwd: path.resolve("src-rust"),
@@ -109,7 +163,13 @@ async function build_rust_aarch64_apple_darwin() {
   }
   await spawnAsync(
     "cargo",
-    ["build", "--release", "--target", "aarch64-apple-darwin"],
+    [
+      "build",
+      "--release",
+      "--target",
+      "aarch64-apple-darwin",
+      ...NATIVE_PACKAGES,
+    ],
     "rust-aarch64-apple-darwin",
     {
       cwd: path.resolve("src-rust"),
@@ -166,9 +226,85 @@ async function lipo_macos_universal() {
     "lipo_macos_universal",
   );
 
+  const addonPath = path.resolve(
+    "native",
+    "macos-universal",
+    "nexp-compiler.node",
+  );
+  if (!existsSync(path.dirname(addonPath))) {
+    mkdirSync(path.dirname(addonPath), { recursive: true });
+  }
+  await spawnAsync(
+    lipoCmd,
+    [
+      "-output",
+      addonPath,
+      "-create",
+      path.resolve(
+        "src-rust",
+        "target",
+        "x86_64-apple-darwin",
+        "release",
+        "libnexp_compiler_napi.dylib",
+      ),
+      path.resolve(
+        "src-rust",
+        "target",
+        "aarch64-apple-darwin",
+        "release",
+        "libnexp_compiler_napi.dylib",
+      ),
+    ],
+    "lipo_macos_universal_addon",
+  );
+
   logger.info("Lipo macOS universal DONE");
 }
 
+async function build_rust_wasm32_unknown_unknown() {
+  logger.info("Build rust-wasm32-unknown-unknown");
+
+  await spawnAsync(
+    "cargo",
+    [
+      "build",
+      "--release",
+      "--target",
+      "wasm32-unknown-unknown",
+      "-p",
+      "nexp-compiler-wasm",
+    ],
+    "rust-wasm32-unknown-unknown",
+    {
+      cwd: path.resolve("src-rust"),
+    },
+  );
+
+  // The wasm-bindgen CLI must match the version of the `wasm-bindgen` crate in Cargo.lock,
+  // this path must match the path in `src/build.ts > findCompilerWasm()` call
+  await spawnAsync(
+    "wasm-bindgen",
+    [
+      "--target",
+      "nodejs",
+      "--out-dir",
+      path.resolve("wasm"),
+      "--out-name",
+      "nexp_compiler_wasm",
+      path.resolve(
+        "src-rust",
+        "target",
+        "wasm32-unknown-unknown",
+        "release",
+        "nexp_compiler_wasm.wasm",
+      ),
+    ],
+    "wasm-bindgen",
+  );
+
+  logger.info("Build rust-wasm32-unknown-unknown DONE");
+}
+
 async function buildRustImpl(skipBuildRustImpl: boolean) {
   if (skipBuildRustImpl) {
     logger.info("Skipping Rust implementation build as requested.");
@@ -182,6 +318,7 @@ async function buildRustImpl(skipBuildRustImpl: boolean) {
   await build_rust_x86_64_apple_darwin();
   await build_rust_aarch64_apple_darwin();
   await lipo_macos_universal();
+  await build_rust_wasm32_unknown_unknown();
 
   logger.info("Rust implementation build completed.");
 }
@@ -226,7 +363,7 @@ async function main() {
 
   const distDirs = ["dist"];
   if (!skipBuildRustImpl) {
-    distDirs.push("rust-dist");
+    distDirs.push("rust-dist", "native", "wasm");
   }
   distDirs.forEach((dir) => {
     const dirPath = path.resolve(dir);
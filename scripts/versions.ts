@@ -10,6 +10,12 @@ import { binaryVersion as tsImplCompilerVer } from "../src/ts-impl-cli-ver";
 import { RELEASE_TAG_NAME } from "../src/post-install/release-tag-name";
 import { logger } from "../src/env-logger";
 
+const RUST_CRATE_DIRS = [
+  "src-rust",
+  path.join("src-rust", "napi"),
+  path.join("src-rust", "wasm"),
+];
+
 export type Versions = {
   mainCli: string;
   compilerTypescript: string;
@@ -70,14 +76,19 @@ export async function setVersions(
     fs.writeFileSync(tsImplCompilerVerFilePath, tsImplCompilerVerFileContent);
   }
 
-  const cargoTomlPath = path.resolve("src-rust", "Cargo.toml");
-  const cargoTomlContent = parseToml(fs.readFileSync(cargoTomlPath, "utf-8"));
-  const cargoTomlPackage = cargoTomlContent.package as TomlTable;
-  cargoTomlPackage.version = versions.compilerRust;
-  if (dryRun) {
-    logger.debug(`Will update Cargo.toml:\n${stringifyToml(cargoTomlContent)}`);
-  } else {
-    fs.writeFileSync(cargoTomlPath, stringifyToml(cargoTomlContent));
+  // The compiler crate, its Node.js addon and its WebAssembly build share the same version
+  for (const crateDir of RUST_CRATE_DIRS) {
+    const cargoTomlPath = path.resolve(crateDir, "Cargo.toml");
+    const cargoTomlContent = parseToml(fs.readFileSync(cargoTomlPath, "utf-8"));
+    const cargoTomlPackage = cargoTomlContent.package as TomlTable;
+    cargoTomlPackage.version = versions.compilerRust;
+    if (dryRun) {
+      logger.debug(
+        `Will update ${cargoTomlPath}:\n${stringifyToml(cargoTomlContent)}`,
+      );
+    } else {
+      fs.writeFileSync(cargoTomlPath, stringifyToml(cargoTomlContent));
+    }
   }
 
   const releaseTagFilePath = path.resolve(
@@ -0,0 +1,193 @@
+use anyhow::Context;
+use napi::{Env, JsObject};
+use napi_derive::napi;
+use serde_json::Value;
+use std::path::Path;
+
+use nexp_compiler_rs::cache::ExportsCache;
+use nexp_compiler_rs::diagnostics::Diagnostic;
+use nexp_compiler_rs::project_config::ProjectConfig;
+use nexp_compiler_rs::vfs::{DiskFs, FileSystem};
+use nexp_compiler_rs::{AppStruct, Config, Convention, manifest, openapi, routes};
+
+/// Options of every function, named and defaulting like the flags of the CLI.
+#[napi(object)]
+#[derive(Default)]
+pub struct CompileOptions {
+    /// `src` by default
+    pub src_dir: Option<String>,
+    /// `nexp-compiled` by default
+    pub dist_dir: Option<String>,
+    /// Name of the generated server, `server.ts` by default
+    pub filename: Option<String>,
+    /// Path to write the route manifest to, only used by `compile`
+    pub manifest: Option<String>,
+    /// Path to write the OpenAPI document to, only used by `compile`
+    pub openapi: Option<String>,
+    pub no_cache: Option<bool>,
+    pub strict: Option<bool>,
+    /// `dispatch` or `native`
+    pub route_style: Option<String>,
+    /// `bundler`, `node-esm` or `commonjs`
+    pub import_style: Option<String>,
+    /// Project config file, `nexp.config.json` next to the source directory if it exists
+    pub config: Option<String>,
+}
+
+/// Options resolved against the project config, as the CLI does.
+struct Compiler {
+    src_dir: String,
+    dist_dir: String,
+    filename: String,
+    manifest: Option<String>,
+    openapi: Option<String>,
+    convention: Convention,
+    config: Config,
+    cache: ExportsCache,
+}
+
+impl Compiler {
+    fn new(options: Option<CompileOptions>) -> anyhow::Result<Self> {
+        let options = options.unwrap_or_default();
+        let src_dir = options.src_dir.unwrap_or_else(|| "src".to_string());
+        let mut convention = Convention::default();
+        let mut config = Config::default();
+        ProjectConfig::load(&DiskFs, options.config.as_deref(), &src_dir)?
+            .apply(&mut convention, &mut config);
+        // Options win over the project config
+        if let Some(route_style) = options.route_style {
+            config.route_style = serde_json::from_value(Value::String(route_style))
+                .context("Invalid `routeStyle` option")?;
+        }
+        if let Some(import_style) = options.import_style {
+            config.import_style = serde_json::from_value(Value::String(import_style))
+                .context("Invalid `importStyle` option")?;
+        }
+        if options.strict == Some(true) {
+            config.strict = true;
+        }
+        convention.apply_import_style(config.import_style);
+
+        let dist_dir = options
+            .dist_dir
+            .unwrap_or_else(|| "nexp-compiled".to_string());
+        let cache = if options.no_cache == Some(true) {
+            ExportsCache::disabled()
+        } else {
+            ExportsCache::load(&DiskFs, &dist_dir, &convention)
+        };
+        Ok(Self {
+            src_dir,
+            dist_dir,
+            filename: options.filename.unwrap_or_else(|| "server.ts".to_string()),
+            manifest: options.manifest,
+            openapi: options.openapi,
+            convention,
+            config,
+            cache,
+        })
+    }
+
+    /// Scan and analyze the source directory without generating the server.
+    fn analyze(&mut self) -> anyhow::Result<AppStruct> {
+        // The cache lives in the dist directory, which may not exist before a first build
+        DiskFs.create_dir_all(Path::new(&self.dist_dir))?;
+        let mut app_struct =
+            nexp_compiler_rs::scan(&DiskFs, &self.src_dir, &self.dist_dir, &self.convention)?;
+        nexp_compiler_rs::analyze(
+            &DiskFs,
+            &mut app_struct,
+            &self.convention,
+            &self.config,
+            &mut self.cache,
+        )?;
+        Ok(app_struct)
+    }
+}
+
+/// `value` with the warnings of `app_struct` as its `warnings`, in the shape of the thrown
+/// `diagnostics`.
+fn with_warnings(mut value: Value, app_struct: &AppStruct) -> Value {
+    if let Value::Object(object) = &mut value {
+        let warnings = app_struct
+            .warnings
+            .iter()
+            .map(|warning| warning.to_json(&DiskFs))
+            .collect();
+        object.insert("warnings".to_string(), Value::Array(warnings));
+    }
+    value
+}
+
+/// Run `f` with the resolved options, turning a failure into a thrown `Error` whose
+/// `diagnostics` are the objects printed by `--message-format json`.
+fn with_compiler(
+    env: Env,
+    options: Option<CompileOptions>,
+    f: impl FnOnce(&mut Compiler) -> anyhow::Result<Value>,
+) -> napi::Result<Value> {
+    // Logs of the compiler, `NEXP_LOG` raises or lowers the level
+    let logger_env = env_logger::Env::new().filter_or("NEXP_LOG", "warn");
+    let _ = env_logger::try_init_from_env(logger_env);
+
+    let result = Compiler::new(options).and_then(|mut compiler| f(&mut compiler));
+    result.or_else(|e| {
+        let diagnostics = Diagnostic::from_error(&e);
+        let message = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&DiskFs))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let json = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.to_json(&DiskFs))
+            .collect::<Vec<_>>();
+        let mut error: JsObject = env.create_error(napi::Error::from_reason(message))?;
+        error.set_named_property("diagnostics", env.to_js_value(&json)?)?;
+        Err(napi::Error::from(error.into_unknown()))
+    })
+}
+
+/// Compile the server like the CLI, returning the route manifest and its `warnings`.
+#[napi]
+pub fn compile(env: Env, options: Option<CompileOptions>) -> napi::Result<Value> {
+    with_compiler(env, options, |compiler| {
+        let app_struct = nexp_compiler_rs::compile(
+            &DiskFs,
+            &compiler.src_dir,
+            &compiler.dist_dir,
+            &compiler.filename,
+            &compiler.convention,
+            &compiler.config,
+            &mut compiler.cache,
+        )?;
+        if let Some(manifest_path) = &compiler.manifest {
+            manifest::write_manifest(&DiskFs, &app_struct, manifest_path)?;
+        }
+        if let Some(openapi_path) = &compiler.openapi {
+            openapi::write_openapi(&DiskFs, &app_struct, openapi_path)?;
+        }
+        let manifest = manifest::build_manifest(&app_struct)?;
+        Ok(with_warnings(serde_json::to_value(manifest)?, &app_struct))
+    })
+}
+
+/// The app directory tree, with the endpoint of every route and the `warnings` at its root.
+#[napi]
+pub fn get_routes(env: Env, options: Option<CompileOptions>) -> napi::Result<Value> {
+    with_compiler(env, options, |compiler| {
+        let app_struct = compiler.analyze()?;
+        let route_tree = serde_json::to_value(routes::route_tree(&app_struct)?)?;
+        Ok(with_warnings(route_tree, &app_struct))
+    })
+}
+
+/// The route manifest and its `warnings`, without generating the server.
+#[napi]
+pub fn get_manifest(env: Env, options: Option<CompileOptions>) -> napi::Result<Value> {
+    with_compiler(env, options, |compiler| {
+        let app_struct = compiler.analyze()?;
+        let manifest = manifest::build_manifest(&app_struct)?;
+        Ok(with_warnings(serde_json::to_value(manifest)?, &app_struct))
+    })
+}
@@ -0,0 +1,188 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::Convention;
+use crate::diagnostics::Code;
+use crate::exports::RouteExports;
+use crate::vfs::FileSystem;
+
+const CACHE_DIR_NAME: &str = ".nexp-cache";
+const CACHE_FILE_NAME: &str = "route-exports.json";
+/// Bump whenever the shape of [`RouteExports`] or the export analysis changes.
+const CACHE_VERSION: u32 = 9;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheEntry {
+    hash: String,
+    /// Content hashes of the modules the route re-exports from, keyed by absolute path
+    dependency_hashes: HashMap<String, String>,
+    exports: RouteExports,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    compiler_version: String,
+    /// Convention values the export analysis depends on
+    fingerprint: String,
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// On-disk cache of parsed route exports, keyed by route path and content hash.
+#[derive(Debug, Default)]
+pub struct ExportsCache {
+    /// `None` when caching is disabled
+    file_path: Option<PathBuf>,
+    fingerprint: String,
+    entries: HashMap<String, CacheEntry>,
+    /// Entries looked up during the current compilation, others are pruned on save
+    used: HashSet<String>,
+    dirty: bool,
+}
+
+fn convention_fingerprint(convention: &Convention) -> String {
+    format!(
+        "{}:{}:{}:{}",
+        convention.openapi_export_name,
+        convention.middlewares_export_name,
+        convention.config_export_name,
+        convention.support_ext.join(",")
+    )
+}
+
+pub(crate) fn hash_source(source: &str) -> String {
+    blake3::hash(source.as_bytes()).to_hex().to_string()
+}
+
+/// Hash the current content of every dependency, `None` if one can no longer be read.
+fn dependency_hashes(
+    fs: &dyn FileSystem,
+    exports: &RouteExports,
+) -> Option<HashMap<String, String>> {
+    exports
+        .dependencies
+        .iter()
+        .map(|path| {
+            let source = fs.read_to_string(Path::new(path)).ok()?;
+            Some((path.clone(), hash_source(&source)))
+        })
+        .collect()
+}
+
+impl ExportsCache {
+    /// A cache that never hits and never touches the file system.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Load the cache stored in `dist_dir`, starting empty if it is missing or stale.
+    pub fn load(fs: &dyn FileSystem, dist_dir: &str, convention: &Convention) -> Self {
+        let file_path = Path::new(dist_dir)
+            .join(CACHE_DIR_NAME)
+            .join(CACHE_FILE_NAME);
+        let fingerprint = convention_fingerprint(convention);
+
+        let entries = fs
+            .read_to_string(&file_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<CacheFile>(&content).ok())
+            .filter(|cache_file| {
+                cache_file.version == CACHE_VERSION
+                    && cache_file.compiler_version == env!("CARGO_PKG_VERSION")
+                    && cache_file.fingerprint == fingerprint
+            })
+            .map(|cache_file| cache_file.entries)
+            .unwrap_or_default();
+        log::debug!(
+            "Loaded {} cached route exports from {}",
+            entries.len(),
+            file_path.display()
+        );
+
+        Self {
+            file_path: Some(file_path),
+            fingerprint,
+            entries,
+            used: HashSet::new(),
+            dirty: false,
+        }
+    }
+
+    pub(crate) fn get(&self, fs: &dyn FileSystem, key: &str, hash: &str) -> Option<RouteExports> {
+        self.file_path.as_ref()?;
+        let entry = self.entries.get(key).filter(|entry| entry.hash == hash)?;
+        let is_fresh = dependency_hashes(fs, &entry.exports)
+            .is_some_and(|dependency_hashes| dependency_hashes == entry.dependency_hashes);
+        is_fresh.then(|| entry.exports.clone())
+    }
+
+    /// Record the exports of a route used by the current compilation.
+    pub(crate) fn insert(
+        &mut self,
+        fs: &dyn FileSystem,
+        key: &str,
+        hash: String,
+        exports: RouteExports,
+    ) {
+        if self.file_path.is_none() {
+            return;
+        }
+        // A module that does not exist yet has no hash to compare, the route is analyzed again
+        // until it can be resolved
+        if exports
+            .warnings
+            .iter()
+            .any(|warning| warning.code == Some(Code::UnresolvedImport))
+        {
+            return;
+        }
+        self.used.insert(key.to_string());
+        let entry = CacheEntry {
+            hash,
+            dependency_hashes: dependency_hashes(fs, &exports).unwrap_or_default(),
+            exports,
+        };
+        if self.entries.get(key) == Some(&entry) {
+            return;
+        }
+        self.entries.insert(key.to_string(), entry);
+        self.dirty = true;
+    }
+
+    /// Persist the cache, dropping entries of routes that were not part of this compilation.
+    pub fn save(&mut self, fs: &dyn FileSystem) -> Result<()> {
+        let Some(file_path) = &self.file_path else {
+            return Ok(());
+        };
+
+        let used = std::mem::take(&mut self.used);
+        let entry_count = self.entries.len();
+        self.entries.retain(|key, _| used.contains(key));
+        if !self.dirty && self.entries.len() == entry_count {
+            return Ok(());
+        }
+
+        if let Some(parent) = file_path.parent() {
+            fs.create_dir_all(parent)?;
+        }
+        let cache_file = CacheFile {
+            version: CACHE_VERSION,
+            compiler_version: env!("CARGO_PKG_VERSION").to_string(),
+            fingerprint: self.fingerprint.clone(),
+            entries: std::mem::take(&mut self.entries),
+        };
+        let json = serde_json::to_string(&cache_file).context("Failed to serialize cache")?;
+        self.entries = cache_file.entries;
+        fs.write(file_path, &json)
+            .with_context(|| format!("Failed to write cache {}", file_path.display()))?;
+        log::debug!(
+            "Saved {} cached route exports to {}",
+            self.entries.len(),
+            file_path.display()
+        );
+        self.dirty = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::diagnostics::{self, Code, Diagnostic};
+use crate::{AppRoute, RouteSegment, app_relative_segments, rel_path_to_endpoint};
+
+/// Segments of the URLs a route directory matches: virtual groups are dropped, parameter
+/// names erased and static names lowercased, as Express routes are case-insensitive.
+fn endpoint_shape(relative_path: &str) -> Vec<String> {
+    app_relative_segments(relative_path)
+        .filter_map(|segment| match RouteSegment::parse(segment) {
+            RouteSegment::Static(name) => Some(name.to_lowercase()),
+            RouteSegment::VirtualGroup(_) => None,
+            RouteSegment::Dynamic(_) => Some(":".to_string()),
+            RouteSegment::CatchAll(_) => Some("*".to_string()),
+            RouteSegment::OptionalCatchAll(_) => Some("{*}".to_string()),
+        })
+        .collect()
+}
+
+/// Fail when several enabled routes match the same URLs, e.g. `app/(a)/users` and
+/// `app/(b)/users`, or `app/posts/[id]` and `app/posts/[slug]`. Only the first route
+/// registered would ever be reached. Every group is reported on its first shadowed route.
+pub fn check_route_conflicts(app: &AppRoute, src_dir: &Path) -> Result<()> {
+    fn traverse(app_route: &AppRoute, routes_by_shape: &mut BTreeMap<Vec<String>, Vec<String>>) {
+        if let Some(route) = app_route.enabled_route() {
+            let source = format!("{}/{}", app_route.relative_path, route);
+            let mut shape = endpoint_shape(&app_route.relative_path);
+            routes_by_shape
+                .entry(shape.clone())
+                .or_default()
+                .push(source.clone());
+            // An optional catch-all also matches the URL of its parent directory
+            if shape.last().is_some_and(|segment| segment == "{*}") {
+                shape.pop();
+                routes_by_shape.entry(shape).or_default().push(source);
+            }
+        }
+        for child in &app_route.children {
+            traverse(child, routes_by_shape);
+        }
+    }
+
+    let mut routes_by_shape = BTreeMap::new();
+    traverse(app, &mut routes_by_shape);
+
+    let mut conflicts: Vec<Vec<String>> = Vec::new();
+    for sources in routes_by_shape.into_values() {
+        // Two optional catch-alls collide on both of their shapes, report them once
+        if sources.len() > 1 && !conflicts.contains(&sources) {
+            conflicts.push(sources);
+        }
+    }
+    let mut errors = Vec::new();
+    for sources in &conflicts {
+        let mut diagnostic = Diagnostic::error(
+            Code::RouteConflict,
+            format!(
+                "Route matches the same URLs as {}, it is never reached",
+                sources[0]
+            ),
+        )
+        .with_file(src_dir.join(&sources[1]));
+        for source in sources {
+            diagnostic =
+                diagnostic.with_note(format!("{} <- {}", rel_path_to_endpoint(source)?, source));
+        }
+        errors.push(diagnostic.into());
+    }
+    diagnostics::check(errors)
+}
@@ -0,0 +1,292 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::vfs::FileSystem;
+
+/// How diagnostics are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum MessageFormat {
+    /// Messages with a codeframe of the offending source line
+    #[default]
+    Human,
+    /// One JSON object per line on stderr, for editors and CI
+    Json,
+}
+
+/// Stable codes of the problems the compiler reports. Never renumber them, tools match on
+/// the codes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Code {
+    /// A source file is not valid JavaScript or TypeScript
+    ParseError,
+    /// A route file exports no handler
+    EmptyRoute,
+    /// An export that is ignored or can not be used, e.g. a misnamed method handler
+    BadExport,
+    /// Several route files match the same URLs
+    RouteConflict,
+    /// A `config` export of a route that can not be applied
+    InvalidRouteConfig,
+    /// A module a route re-exports from that can not be resolved or analyzed
+    UnresolvedImport,
+    /// A project config file that can not be read or applied
+    InvalidProjectConfig,
+    /// An `openapi` export of a route that is not an object
+    InvalidOpenApi,
+}
+
+impl Code {
+    pub fn id(self) -> &'static str {
+        match self {
+            Code::ParseError => "NEXP001",
+            Code::EmptyRoute => "NEXP002",
+            Code::BadExport => "NEXP003",
+            Code::RouteConflict => "NEXP004",
+            Code::InvalidRouteConfig => "NEXP005",
+            Code::UnresolvedImport => "NEXP006",
+            Code::InvalidProjectConfig => "NEXP007",
+            Code::InvalidOpenApi => "NEXP008",
+        }
+    }
+
+    pub fn category(self) -> &'static str {
+        match self {
+            Code::ParseError => "parse-error",
+            Code::EmptyRoute => "empty-route",
+            Code::BadExport => "bad-export",
+            Code::RouteConflict => "route-conflict",
+            Code::InvalidRouteConfig => "invalid-route-config",
+            Code::UnresolvedImport => "unresolved-import",
+            Code::InvalidProjectConfig => "invalid-project-config",
+            Code::InvalidOpenApi => "invalid-openapi",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 1-based line and column (in characters) of a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    /// `None` for failures outside of the analyzed sources, e.g. an unreadable directory
+    pub code: Option<Code>,
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub position: Option<Position>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonDiagnostic<'a> {
+    severity: Severity,
+    code: Option<&'static str>,
+    category: Option<&'static str>,
+    message: &'a str,
+    file: Option<String>,
+    line: Option<usize>,
+    column: Option<usize>,
+    notes: &'a [String],
+    /// The human rendering, with its codeframe
+    rendered: String,
+}
+
+/// Shorten paths below the current directory, which is where the user runs the CLI from.
+fn display_path(path: &Path) -> String {
+    std::env::current_dir()
+        .ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok())
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
+
+impl Diagnostic {
+    pub fn error(code: Code, message: impl Into<String>) -> Self {
+        Self {
+            code: Some(code),
+            severity: Severity::Error,
+            message: message.into(),
+            file: None,
+            position: None,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(code: Code, message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            ..Self::error(code, message)
+        }
+    }
+
+    pub fn with_file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    pub fn with_position(mut self, position: Option<Position>) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    /// The diagnostics an error stands for, uncategorized unless it came from a diagnostic.
+    pub fn from_error(error: &anyhow::Error) -> Vec<Diagnostic> {
+        if let Some(diagnostic) = error.downcast_ref::<Diagnostic>() {
+            return vec![diagnostic.clone()];
+        }
+        if let Some(diagnostics) = error.downcast_ref::<Diagnostics>() {
+            return diagnostics.0.clone();
+        }
+        vec![Diagnostic {
+            code: None,
+            severity: Severity::Error,
+            message: format!("{:#}", error),
+            file: None,
+            position: None,
+            notes: Vec::new(),
+        }]
+    }
+
+    fn location(&self) -> Option<String> {
+        let file = display_path(self.file.as_ref()?);
+        Some(match self.position {
+            Some(position) => format!("{}:{}:{}", file, position.line, position.column),
+            None => file,
+        })
+    }
+
+    /// The message, its location and a codeframe pointing at the offending source, read
+    /// from `fs`.
+    pub fn render(&self, fs: &dyn FileSystem) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let mut rendered = match self.code {
+            Some(code) => format!("{}[{}]: {}", severity, code.id(), self.message),
+            None => format!("{}: {}", severity, self.message),
+        };
+        if let Some(location) = self.location() {
+            rendered.push_str(&format!("\n  --> {}", location));
+        }
+
+        let source_line = self
+            .file
+            .as_ref()
+            .zip(self.position)
+            .and_then(|(file, position)| {
+                let source = fs.read_to_string(file).ok()?;
+                let line = source
+                    .lines()
+                    .nth(position.line.checked_sub(1)?)?
+                    .to_string();
+                Some((line, position))
+            });
+        if let Some((line, position)) = source_line {
+            let gutter = position.line.to_string();
+            let padding = " ".repeat(gutter.len());
+            // Keep tabs so that the caret lines up with the source
+            let caret_offset = line
+                .chars()
+                .take(position.column.saturating_sub(1))
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            rendered.push_str(&format!(
+                "\n{} |\n{} | {}\n{} | {}^",
+                padding, gutter, line, padding, caret_offset
+            ));
+        }
+        for note in &self.notes {
+            rendered.push_str(&format!("\n  = note: {}", note));
+        }
+        rendered
+    }
+
+    /// The object printed by `--message-format json`.
+    pub fn to_json(&self, fs: &dyn FileSystem) -> serde_json::Value {
+        let json = JsonDiagnostic {
+            severity: self.severity,
+            code: self.code.map(Code::id),
+            category: self.code.map(Code::category),
+            message: &self.message,
+            file: self.file.as_deref().map(display_path),
+            line: self.position.map(|position| position.line),
+            column: self.position.map(|position| position.column),
+            notes: &self.notes,
+            rendered: self.render(fs),
+        };
+        serde_json::to_value(json).unwrap_or_default()
+    }
+
+    /// The diagnostic as printed in `message_format`, with the codeframe read from `fs`.
+    pub fn format(&self, fs: &dyn FileSystem, message_format: MessageFormat) -> String {
+        match message_format {
+            MessageFormat::Human => self.render(fs),
+            MessageFormat::Json => self.to_json(fs).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.location() {
+            Some(location) => write!(f, "{}: {}", location, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Several problems failing the compilation together, e.g. every conflicting route.
+#[derive(Debug, Clone)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages = self.0.iter().map(|d| d.to_string()).collect::<Vec<_>>();
+        write!(f, "{}", messages.join("\n"))
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+/// Fail with every error among `errors`, if any.
+pub fn check(errors: Vec<anyhow::Error>) -> anyhow::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let diagnostics = errors.iter().flat_map(Diagnostic::from_error).collect();
+    Err(Diagnostics(diagnostics).into())
+}
+
+/// `error` along with the `warnings` found before it, which are reported first.
+pub fn with_warnings(warnings: Vec<Diagnostic>, error: anyhow::Error) -> anyhow::Error {
+    if warnings.is_empty() {
+        return error;
+    }
+    let diagnostics = warnings
+        .into_iter()
+        .chain(Diagnostic::from_error(&error))
+        .collect();
+    Diagnostics(diagnostics).into()
+}
@@ -0,0 +1,913 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use swc_common::comments::{CommentKind, Comments, SingleThreadedComments};
+use swc_common::sync::Lrc;
+use swc_common::{BytePos, Spanned};
+use swc_common::{FileName, SourceMap};
+use swc_ecma_ast::*;
+use swc_ecma_parser::{EsSyntax, Parser, StringInput, Syntax, TsSyntax, lexer::Lexer};
+use swc_ecma_visit::{Visit, VisitWith};
+
+use crate::Convention;
+use crate::diagnostics::{Code, Diagnostic, Position};
+use crate::vfs::FileSystem;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHandler {
+    pub export_name: String,
+    pub is_async: bool,
+    /// Leading JSDoc/TSDoc comment of the export, without the comment markers
+    pub doc: Option<String>,
+    /// Where the route file exports the handler
+    #[serde(default)]
+    pub position: Option<Position>,
+}
+
+/// Everything a route file exports that the compiler cares about.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RouteExports {
+    pub handlers: Vec<EndpointHandler>,
+    /// Statically evaluated value of the `openapi` export, merged into the generated document
+    pub openapi: Option<serde_json::Value>,
+    /// Statically evaluated value of the `config` export, the route config
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+    /// Absolute paths of the local modules the exports were re-exported from
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    /// Exported middleware arrays, `middlewares` for the whole route
+    /// and `<METHOD>_middlewares` for a single method
+    #[serde(default)]
+    pub middlewares: Vec<String>,
+    /// Problems found in the route file and the modules it re-exports from, reported
+    /// by the analysis in traversal order, so that cached routes report them as well
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// A top-level binding that `export { local as NAME }` can refer to.
+#[derive(Clone)]
+enum LocalBinding<'a> {
+    Decl {
+        is_async: bool,
+        pos: BytePos,
+        init: Option<&'a Expr>,
+    },
+    /// `import { imported as local } from "specifier"`, `imported` is "default" for default imports
+    Import { specifier: String, imported: String },
+}
+
+/// Where the value of an export specifier comes from.
+enum ExportOrigin {
+    Local(String),
+    Module { specifier: String, name: String },
+}
+
+struct ExportVisitor<'a> {
+    fs: &'a dyn FileSystem,
+    abs_path: &'a Path,
+    source_map: &'a SourceMap,
+    comments: &'a SingleThreadedComments,
+    convention: &'a Convention,
+    /// This module and the ones up the re-export chain, used to stop on circular re-exports
+    stack: Vec<PathBuf>,
+    locals: HashMap<String, LocalBinding<'a>>,
+    /// Exports of the re-exported modules, keyed by resolved path
+    modules: HashMap<PathBuf, Option<RouteExports>>,
+    /// Handlers from `export * from`, explicit exports take precedence over them
+    star_exports: RouteExports,
+    exports: RouteExports,
+}
+
+impl<'a> ExportVisitor<'a> {
+    fn new(
+        fs: &'a dyn FileSystem,
+        abs_path: &'a Path,
+        source_map: &'a SourceMap,
+        module: &'a Module,
+        comments: &'a SingleThreadedComments,
+        convention: &'a Convention,
+        parents: &'a [PathBuf],
+    ) -> Self {
+        Self {
+            fs,
+            abs_path,
+            source_map,
+            comments,
+            convention,
+            stack: parents
+                .iter()
+                .cloned()
+                .chain([canonical_path(fs, abs_path)])
+                .collect(),
+            locals: collect_locals(module),
+            modules: HashMap::new(),
+            star_exports: RouteExports::default(),
+            exports: RouteExports::default(),
+        }
+    }
+
+    /// The closest `/** ... */` comment in front of `pos`.
+    fn doc_comment(&self, pos: BytePos) -> Option<String> {
+        let comments = self.comments.get_leading(pos)?;
+        let comment = comments
+            .iter()
+            .rev()
+            .find(|c| c.kind == CommentKind::Block && c.text.starts_with('*'))?;
+        let doc = comment
+            .text
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                line.strip_prefix('*').unwrap_or(line).trim()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+        if doc.is_empty() { None } else { Some(doc) }
+    }
+
+    fn position(&self, pos: BytePos) -> Option<Position> {
+        source_position(self.source_map, pos)
+    }
+
+    fn warn(&mut self, code: Code, message: String, pos: BytePos) {
+        let warning = Diagnostic::warning(code, message)
+            .with_file(self.abs_path)
+            .with_position(self.position(pos));
+        self.exports.warnings.push(warning);
+    }
+
+    fn push_handler(&mut self, export_name: String, is_async: bool, pos: BytePos) {
+        let doc = self.doc_comment(pos);
+        self.exports.handlers.push(EndpointHandler {
+            export_name,
+            is_async,
+            doc,
+            position: self.position(pos),
+        });
+    }
+
+    /// Whether `name` is an export whose value is evaluated statically instead of routed.
+    fn is_static_export(&self, name: &str) -> bool {
+        name == self.convention.openapi_export_name || name == self.convention.config_export_name
+    }
+
+    fn set_static_export(&mut self, name: &str, init: Option<&Expr>) {
+        let value = init.and_then(|init| {
+            let value = static_json_value(init);
+            if value.is_none() {
+                self.warn(
+                    Code::BadExport,
+                    format!("Ignoring `{}` export, it must be a JSON-like literal", name),
+                    init.span_lo(),
+                );
+            }
+            // Its keys patch the path item and operations of the OpenAPI document
+            if name == self.convention.openapi_export_name
+                && value.as_ref().is_some_and(|value| !value.is_object())
+            {
+                self.warn(
+                    Code::InvalidOpenApi,
+                    format!("Ignoring `{}` export, it must be an object", name),
+                    init.span_lo(),
+                );
+                return None;
+            }
+            value
+        });
+        *static_export_mut(self.convention, &mut self.exports, name) = value;
+    }
+
+    /// Analyze the module a relative specifier points to, once per module.
+    /// Returns `None` for package imports and modules that cannot be analyzed.
+    fn module_exports(&mut self, specifier: &str, pos: BytePos) -> Option<RouteExports> {
+        if !specifier.starts_with('.') {
+            return None;
+        }
+        let Some(path) = resolve_module_path(
+            self.fs,
+            self.abs_path,
+            specifier,
+            &self.convention.support_ext,
+        ) else {
+            self.warn(
+                Code::UnresolvedImport,
+                format!("Cannot resolve \"{}\"", specifier),
+                pos,
+            );
+            return None;
+        };
+        if let Some(exports) = self.modules.get(&path) {
+            return exports.clone();
+        }
+
+        if self.stack.contains(&path) {
+            self.warn(
+                Code::UnresolvedImport,
+                format!("Circular re-export of {}", path.display()),
+                pos,
+            );
+            self.modules.insert(path, None);
+            return None;
+        }
+
+        let result = self
+            .fs
+            .read_to_string(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|source| {
+                analyze_module(self.fs, &path, source, self.convention, &self.stack)
+            });
+        let exports = match result {
+            Ok(mut exports) => {
+                self.exports.warnings.append(&mut exports.warnings);
+                Some(exports)
+            }
+            Err(e) => {
+                self.warn(
+                    Code::UnresolvedImport,
+                    format!("Failed to analyze {}: {:#}", path.display(), e),
+                    pos,
+                );
+                None
+            }
+        };
+
+        let dependencies = std::iter::once(path.to_string_lossy().to_string()).chain(
+            exports
+                .iter()
+                .flat_map(|exports| exports.dependencies.iter().cloned()),
+        );
+        for dependency in dependencies {
+            if !self.exports.dependencies.contains(&dependency) {
+                self.exports.dependencies.push(dependency);
+            }
+        }
+        self.modules.insert(path, exports.clone());
+        exports
+    }
+
+    /// Record `export { origin as exported }`.
+    fn export_specifier(&mut self, exported: String, origin: ExportOrigin, pos: BytePos) {
+        let is_static = self.is_static_export(&exported);
+        let origin = match origin {
+            ExportOrigin::Local(local) => match self.locals.get(&local).cloned() {
+                Some(LocalBinding::Decl {
+                    is_async,
+                    pos: decl_pos,
+                    init,
+                }) => {
+                    if is_static {
+                        self.set_static_export(&exported, init);
+                    } else {
+                        // The comment on the export wins over the one on the declaration
+                        let doc = self.doc_comment(pos).or_else(|| self.doc_comment(decl_pos));
+                        self.exports.handlers.push(EndpointHandler {
+                            export_name: exported,
+                            is_async,
+                            doc,
+                            position: self.position(pos),
+                        });
+                    }
+                    return;
+                }
+                Some(LocalBinding::Import {
+                    specifier,
+                    imported,
+                }) => ExportOrigin::Module {
+                    specifier,
+                    name: imported,
+                },
+                None => {
+                    self.warn(
+                        Code::BadExport,
+                        format!("Cannot find the declaration of exported `{}`", local),
+                        pos,
+                    );
+                    ExportOrigin::Local(local)
+                }
+            },
+            origin => origin,
+        };
+
+        let module_export = match &origin {
+            ExportOrigin::Module { specifier, name } => self
+                .module_exports(specifier, pos)
+                .map(|exports| (exports, name.as_str())),
+            ExportOrigin::Local(_) => None,
+        };
+        if is_static {
+            let value = module_export.and_then(|(mut exports, name)| {
+                (name == exported)
+                    .then(|| static_export_mut(self.convention, &mut exports, name).take())
+                    .flatten()
+            });
+            *static_export_mut(self.convention, &mut self.exports, &exported) = value;
+            return;
+        }
+        let handler = module_export.and_then(|(exports, name)| {
+            exports
+                .handlers
+                .into_iter()
+                .find(|handler| handler.export_name == name)
+        });
+        let doc = self.doc_comment(pos);
+        self.exports.handlers.push(match handler {
+            Some(handler) => EndpointHandler {
+                export_name: exported,
+                is_async: handler.is_async,
+                doc: doc.or(handler.doc),
+                position: self.position(pos),
+            },
+            // Unknown origin, awaiting a value that is not a promise is harmless
+            None => EndpointHandler {
+                export_name: exported,
+                is_async: true,
+                doc,
+                position: self.position(pos),
+            },
+        });
+    }
+
+    /// Record `exports.NAME = value`, `module.exports.NAME = value` or a property of
+    /// `module.exports = { ... }`.
+    fn commonjs_export(&mut self, name: String, value: &Expr, pos: BytePos) {
+        if self.is_static_export(&name) {
+            self.set_static_export(&name, Some(value));
+            return;
+        }
+        match value {
+            Expr::Ident(ident) => {
+                self.export_specifier(name, ExportOrigin::Local(ident.sym.to_string()), pos)
+            }
+            Expr::Fn(fn_expr) => self.push_handler(name, fn_expr.function.is_async, pos),
+            value => self.push_handler(name, is_async_init(value), pos),
+        }
+    }
+
+    /// Find the CommonJS exports among the top-level statements.
+    fn visit_commonjs_exports(&mut self, module: &Module) {
+        for item in &module.body {
+            let ModuleItem::Stmt(Stmt::Expr(ExprStmt { expr, .. })) = item else {
+                continue;
+            };
+            let Expr::Assign(AssignExpr {
+                op: AssignOp::Assign,
+                left: AssignTarget::Simple(SimpleAssignTarget::Member(member)),
+                right,
+                ..
+            }) = expr.as_ref()
+            else {
+                continue;
+            };
+            let pos = item.span_lo();
+            if is_commonjs_exports(&member.obj)
+                && let MemberProp::Ident(prop) = &member.prop
+            {
+                self.commonjs_export(prop.sym.to_string(), right, pos);
+                continue;
+            }
+            if !is_commonjs_exports(&Expr::Member(member.clone())) {
+                continue;
+            }
+            // `module.exports = value` is what `import value from` gets
+            let Expr::Object(object) = right.as_ref() else {
+                self.commonjs_export("default".to_string(), right, pos);
+                continue;
+            };
+            for prop in &object.props {
+                let PropOrSpread::Prop(prop) = prop else {
+                    continue;
+                };
+                match prop.as_ref() {
+                    Prop::Shorthand(ident) => self.export_specifier(
+                        ident.sym.to_string(),
+                        ExportOrigin::Local(ident.sym.to_string()),
+                        pos,
+                    ),
+                    Prop::KeyValue(kv) => {
+                        if let Some(name) = prop_name(&kv.key) {
+                            self.commonjs_export(name, &kv.value, pos);
+                        }
+                    }
+                    Prop::Method(method) => {
+                        if let Some(name) = prop_name(&method.key) {
+                            self.push_handler(name, method.function.is_async, pos);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn is_middlewares_export(&self, name: &str) -> bool {
+        let export_name = &self.convention.middlewares_export_name;
+        name == export_name
+            || name
+                .strip_suffix(export_name.as_str())
+                .is_some_and(|prefix| prefix.len() > 1 && prefix.ends_with('_'))
+    }
+
+    fn finish(mut self) -> RouteExports {
+        let star_exports = std::mem::take(&mut self.star_exports);
+        for handler in star_exports.handlers {
+            if !self
+                .exports
+                .handlers
+                .iter()
+                .any(|h| h.export_name == handler.export_name)
+            {
+                self.exports.handlers.push(handler);
+            }
+        }
+        if self.exports.openapi.is_none() {
+            self.exports.openapi = star_exports.openapi;
+        }
+        if self.exports.config.is_none() {
+            self.exports.config = star_exports.config;
+        }
+
+        let (middlewares, handlers) = std::mem::take(&mut self.exports.handlers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|handler| self.is_middlewares_export(&handler.export_name));
+        self.exports.handlers = handlers;
+        self.exports.middlewares = middlewares
+            .into_iter()
+            .map(|handler| handler.export_name)
+            .collect();
+        for name in star_exports.middlewares {
+            if !self.exports.middlewares.contains(&name) {
+                self.exports.middlewares.push(name);
+            }
+        }
+        self.exports
+    }
+}
+
+impl Visit for ExportVisitor<'_> {
+    fn visit_module(&mut self, n: &Module) {
+        self.visit_commonjs_exports(n);
+        n.visit_children_with(self);
+    }
+
+    fn visit_export_decl(&mut self, n: &ExportDecl) {
+        match &n.decl {
+            Decl::Fn(fn_decl) => {
+                let name = fn_decl.ident.sym.to_string();
+                let is_async = fn_decl.function.is_async;
+                self.push_handler(name, is_async, n.span_lo());
+            }
+            Decl::Var(var_decl) => {
+                for decl in &var_decl.decls {
+                    if let Pat::Ident(ident) = &decl.name {
+                        let name = ident.id.sym.to_string();
+                        if self.is_static_export(&name) {
+                            self.set_static_export(&name, decl.init.as_deref());
+                            continue;
+                        }
+                        let is_async = decl.init.as_deref().is_some_and(is_async_init);
+                        self.push_handler(name, is_async, n.span_lo());
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_export_default_expr(&mut self, n: &ExportDefaultExpr) {
+        let is_async = match n.expr.as_ref() {
+            Expr::Arrow(arrow) => arrow.is_async,
+            Expr::Fn(fn_expr) => fn_expr.function.is_async,
+            // `export default handler`
+            Expr::Ident(ident) => {
+                let origin = ExportOrigin::Local(ident.sym.to_string());
+                self.export_specifier("default".to_string(), origin, n.span_lo());
+                return;
+            }
+            _ => false,
+        };
+        self.push_handler("default".to_string(), is_async, n.span_lo());
+    }
+
+    fn visit_export_default_decl(&mut self, n: &ExportDefaultDecl) {
+        let is_async = match &n.decl {
+            DefaultDecl::Fn(fn_expr) => fn_expr.function.is_async,
+            // A class can not be called as a request handler
+            DefaultDecl::Class(_) | DefaultDecl::TsInterfaceDecl(_) => return,
+        };
+        self.push_handler("default".to_string(), is_async, n.span_lo());
+    }
+
+    fn visit_named_export(&mut self, n: &NamedExport) {
+        if n.type_only {
+            return;
+        }
+        let src = n.src.as_ref().map(|src| src.value.to_string());
+        for specifier in &n.specifiers {
+            // `export * as ns from` exposes a module namespace, never a handler
+            let ExportSpecifier::Named(named) = specifier else {
+                continue;
+            };
+            if named.is_type_only {
+                continue;
+            }
+            let orig = module_export_name(&named.orig);
+            let exported = named
+                .exported
+                .as_ref()
+                .map(module_export_name)
+                .unwrap_or_else(|| orig.clone());
+            let origin = match &src {
+                Some(specifier) => ExportOrigin::Module {
+                    specifier: specifier.clone(),
+                    name: orig,
+                },
+                None => ExportOrigin::Local(orig),
+            };
+            self.export_specifier(exported, origin, n.span_lo());
+        }
+    }
+
+    fn visit_export_all(&mut self, n: &ExportAll) {
+        if n.type_only {
+            return;
+        }
+        let specifier = n.src.value.to_string();
+        let Some(exports) = self.module_exports(&specifier, n.span_lo()) else {
+            self.warn(
+                Code::UnresolvedImport,
+                format!(
+                    "Cannot follow `export * from \"{}\"`, its handlers are not routed",
+                    specifier
+                ),
+                n.span_lo(),
+            );
+            return;
+        };
+        // `export *` never re-exports the default export
+        let position = self.position(n.span_lo());
+        self.star_exports.handlers.extend(
+            exports
+                .handlers
+                .into_iter()
+                .filter(|handler| handler.export_name != "default")
+                .map(|handler| EndpointHandler {
+                    position,
+                    ..handler
+                }),
+        );
+        if self.star_exports.openapi.is_none() {
+            self.star_exports.openapi = exports.openapi;
+        }
+        if self.star_exports.config.is_none() {
+            self.star_exports.config = exports.config;
+        }
+        self.star_exports.middlewares.extend(exports.middlewares);
+    }
+}
+
+/// The field holding the value of a statically evaluated export.
+fn static_export_mut<'e>(
+    convention: &Convention,
+    exports: &'e mut RouteExports,
+    name: &str,
+) -> &'e mut Option<serde_json::Value> {
+    if name == convention.openapi_export_name {
+        &mut exports.openapi
+    } else {
+        &mut exports.config
+    }
+}
+
+/// Whether `expr` is `exports` or `module.exports`.
+fn is_commonjs_exports(expr: &Expr) -> bool {
+    match expr {
+        Expr::Ident(ident) => ident.sym == *"exports",
+        Expr::Member(MemberExpr {
+            obj,
+            prop: MemberProp::Ident(prop),
+            ..
+        }) => {
+            prop.sym == *"exports"
+                && matches!(obj.as_ref(), Expr::Ident(obj) if obj.sym == *"module")
+        }
+        _ => false,
+    }
+}
+
+fn prop_name(key: &PropName) -> Option<String> {
+    match key {
+        PropName::Ident(ident) => Some(ident.sym.to_string()),
+        PropName::Str(s) => Some(s.value.to_string()),
+        _ => None,
+    }
+}
+
+fn module_export_name(name: &ModuleExportName) -> String {
+    match name {
+        ModuleExportName::Ident(ident) => ident.sym.to_string(),
+        ModuleExportName::Str(s) => s.value.to_string(),
+    }
+}
+
+fn is_async_init(init: &Expr) -> bool {
+    matches!(init, Expr::Arrow(arrow) if arrow.is_async)
+}
+
+/// Top-level declarations and imports of a module, by local name.
+fn collect_locals(module: &Module) -> HashMap<String, LocalBinding<'_>> {
+    let mut locals = HashMap::new();
+    for item in &module.body {
+        let (decl, pos) = match item {
+            ModuleItem::Stmt(Stmt::Decl(decl)) => (decl, decl.span_lo()),
+            ModuleItem::ModuleDecl(ModuleDecl::ExportDecl(export_decl)) => {
+                (&export_decl.decl, export_decl.span_lo())
+            }
+            ModuleItem::ModuleDecl(ModuleDecl::Import(import_decl)) if !import_decl.type_only => {
+                let specifier = import_decl.src.value.to_string();
+                for import_specifier in &import_decl.specifiers {
+                    let (local, imported) = match import_specifier {
+                        ImportSpecifier::Named(named) if !named.is_type_only => (
+                            named.local.sym.to_string(),
+                            named
+                                .imported
+                                .as_ref()
+                                .map(module_export_name)
+                                .unwrap_or_else(|| named.local.sym.to_string()),
+                        ),
+                        ImportSpecifier::Default(default) => {
+                            (default.local.sym.to_string(), "default".to_string())
+                        }
+                        _ => continue,
+                    };
+                    locals.insert(
+                        local,
+                        LocalBinding::Import {
+                            specifier: specifier.clone(),
+                            imported,
+                        },
+                    );
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        match decl {
+            Decl::Fn(fn_decl) => {
+                locals.insert(
+                    fn_decl.ident.sym.to_string(),
+                    LocalBinding::Decl {
+                        is_async: fn_decl.function.is_async,
+                        pos,
+                        init: None,
+                    },
+                );
+            }
+            Decl::Var(var_decl) => {
+                for decl in &var_decl.decls {
+                    if let Pat::Ident(ident) = &decl.name {
+                        locals.insert(
+                            ident.id.sym.to_string(),
+                            LocalBinding::Decl {
+                                is_async: decl.init.as_deref().is_some_and(is_async_init),
+                                pos,
+                                init: decl.init.as_deref(),
+                            },
+                        );
+                    }
+                }
+            }
+            Decl::Class(class_decl) => {
+                locals.insert(
+                    class_decl.ident.sym.to_string(),
+                    LocalBinding::Decl {
+                        is_async: false,
+                        pos,
+                        init: None,
+                    },
+                );
+            }
+            _ => {}
+        }
+    }
+    locals
+}
+
+fn canonical_path(fs: &dyn FileSystem, path: &Path) -> PathBuf {
+    fs.canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Resolve a relative module specifier to a source file, trying the exact path,
+/// the path with each supported extension, `.js` specifiers pointing at `.ts` files
+/// and directory index files.
+fn resolve_module_path(
+    fs: &dyn FileSystem,
+    from: &Path,
+    specifier: &str,
+    support_ext: &[String],
+) -> Option<PathBuf> {
+    let base = from.parent()?.join(specifier);
+    let with_ext = |path: &Path, ext: &str| {
+        let mut path = OsString::from(path);
+        path.push(ext);
+        PathBuf::from(path)
+    };
+
+    let mut candidates = vec![base.clone()];
+    candidates.extend(support_ext.iter().map(|ext| with_ext(&base, ext)));
+    // `./handler.js` may point at `handler.ts`, `./handler.mjs` at `handler.mts`
+    if let Some(ext) = base.extension()
+        && support_ext.iter().any(|e| e[1..] == *ext)
+    {
+        let stem = base.with_extension("");
+        candidates.extend(support_ext.iter().map(|ext| with_ext(&stem, ext)));
+    }
+    candidates.extend(
+        support_ext
+            .iter()
+            .map(|ext| base.join(format!("index{}", ext))),
+    );
+
+    candidates
+        .into_iter()
+        .find(|candidate| fs.is_file(candidate))
+        .map(|path| canonical_path(fs, &path))
+}
+
+/// Evaluate a literal expression (objects, arrays, strings, numbers, booleans, null)
+/// into JSON without running any code. Returns `None` for anything dynamic.
+fn static_json_value(expr: &Expr) -> Option<serde_json::Value> {
+    use serde_json::Value;
+
+    match expr {
+        Expr::Paren(paren) => static_json_value(&paren.expr),
+        Expr::TsAs(ts_as) => static_json_value(&ts_as.expr),
+        Expr::TsConstAssertion(assertion) => static_json_value(&assertion.expr),
+        Expr::TsSatisfies(satisfies) => static_json_value(&satisfies.expr),
+        Expr::Lit(Lit::Str(s)) => Some(Value::String(s.value.to_string())),
+        Expr::Lit(Lit::Bool(b)) => Some(Value::Bool(b.value)),
+        Expr::Lit(Lit::Null(_)) => Some(Value::Null),
+        Expr::Lit(Lit::Num(n)) => json_number(n.value),
+        Expr::Unary(UnaryExpr {
+            op: UnaryOp::Minus,
+            arg,
+            ..
+        }) => match arg.as_ref() {
+            Expr::Lit(Lit::Num(n)) => json_number(-n.value),
+            _ => None,
+        },
+        Expr::Tpl(tpl) if tpl.exprs.is_empty() => tpl
+            .quasis
+            .first()
+            .and_then(|q| q.cooked.as_ref())
+            .map(|cooked| Value::String(cooked.to_string())),
+        Expr::Array(array) => array
+            .elems
+            .iter()
+            .map(|elem| match elem {
+                Some(ExprOrSpread { spread: None, expr }) => static_json_value(expr),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array),
+        Expr::Object(object) => {
+            let mut map = serde_json::Map::new();
+            for prop in &object.props {
+                let PropOrSpread::Prop(prop) = prop else {
+                    return None;
+                };
+                let Prop::KeyValue(kv) = prop.as_ref() else {
+                    return None;
+                };
+                let key = match &kv.key {
+                    PropName::Ident(ident) => ident.sym.to_string(),
+                    PropName::Str(s) => s.value.to_string(),
+                    PropName::Num(n) => n.value.to_string(),
+                    _ => return None,
+                };
+                map.insert(key, static_json_value(&kv.value)?);
+            }
+            Some(Value::Object(map))
+        }
+        _ => None,
+    }
+}
+
+/// Integral numbers stay integers, so that `1024` is not read back as `1024.0`.
+fn json_number(value: f64) -> Option<serde_json::Value> {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        Some(serde_json::Value::from(value as i64))
+    } else {
+        serde_json::Number::from_f64(value).map(serde_json::Value::Number)
+    }
+}
+
+pub fn get_endpoint_handlers(
+    fs: &dyn FileSystem,
+    abs_path: &Path,
+    convention: &Convention,
+) -> Result<RouteExports> {
+    let source = fs.read_to_string(abs_path)?;
+    parse_route_exports(fs, abs_path, source, convention)
+}
+
+/// Same as [`get_endpoint_handlers`], for a route file whose content is already loaded.
+pub fn parse_route_exports(
+    fs: &dyn FileSystem,
+    abs_path: &Path,
+    source: String,
+    convention: &Convention,
+) -> Result<RouteExports> {
+    log::debug!("Parsing endpoint handlers from: {}", abs_path.display());
+    let exports = analyze_module(fs, abs_path, source, convention, &[])?;
+
+    log::debug!(
+        "Found {} endpoint handlers in {}",
+        exports.handlers.len(),
+        abs_path.display()
+    );
+    for handler in &exports.handlers {
+        log::debug!("  - {} (async: {})", handler.export_name, handler.is_async);
+    }
+
+    Ok(exports)
+}
+
+fn source_position(source_map: &SourceMap, pos: BytePos) -> Option<Position> {
+    let loc = source_map.try_lookup_char_pos(pos).ok()?;
+    Some(Position {
+        line: loc.line,
+        column: loc.col.0 + 1,
+    })
+}
+
+/// Parser syntax matching the extension of a source file.
+fn syntax_for_path(path: &Path) -> Syntax {
+    let ext = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+    match ext {
+        "js" | "jsx" | "mjs" | "cjs" => Syntax::Es(EsSyntax {
+            // `.js` files may contain JSX as well
+            jsx: matches!(ext, "js" | "jsx"),
+            decorators: true,
+            ..Default::default()
+        }),
+        _ => Syntax::Typescript(TsSyntax {
+            tsx: ext == "tsx",
+            decorators: true,
+            dts: false,
+            no_early_errors: true,
+            // `<T>value` is a type assertion everywhere but in `.tsx`
+            disallow_ambiguous_jsx_like: ext != "tsx",
+        }),
+    }
+}
+
+fn analyze_module(
+    fs: &dyn FileSystem,
+    abs_path: &Path,
+    source: String,
+    convention: &Convention,
+    parents: &[PathBuf],
+) -> Result<RouteExports> {
+    let source_map: Lrc<SourceMap> = Default::default();
+    let source_file =
+        source_map.new_source_file(Lrc::new(FileName::Real(abs_path.to_path_buf())), source);
+    let comments = SingleThreadedComments::default();
+
+    let lexer = Lexer::new(
+        syntax_for_path(abs_path),
+        EsVersion::Es2022,
+        StringInput::from(&*source_file),
+        Some(&comments),
+    );
+
+    let mut parser = Parser::new_from(lexer);
+    let module = parser.parse_module().map_err(|e| {
+        Diagnostic::error(Code::ParseError, e.kind().msg())
+            .with_file(abs_path)
+            .with_position(source_position(&source_map, e.span_lo()))
+    })?;
+
+    let mut visitor = ExportVisitor::new(
+        fs,
+        abs_path,
+        &source_map,
+        &module,
+        &comments,
+        convention,
+        parents,
+    );
+    module.visit_with(&mut visitor);
+    Ok(visitor.finish())
+}
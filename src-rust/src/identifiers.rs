@@ -0,0 +1,119 @@
+use std::collections::HashSet;
+
+/// Names declared by the default server template and by the generated code around the
+/// route aliases.
+const TEMPLATE_NAMES: [&str; 10] = [
+    "express",
+    "app",
+    "createServer",
+    "appSettings",
+    "setting",
+    "topLevelMiddlewares",
+    "tailMiddlewares",
+    "require",
+    "module",
+    "exports",
+];
+
+/// Reserved words and restricted names that can not be declared by a module.
+const RESERVED_WORDS: [&str; 48] = [
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Turn any string into a valid JavaScript identifier. Characters outside of ASCII are
+/// spelled out as their code point, so that `用户` and `订单` stay distinct.
+fn to_identifier(raw: &str) -> String {
+    let mut identifier = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '$' => identifier.push(c),
+            c if c.is_ascii() => identifier.push('_'),
+            c => identifier.push_str(&format!("u{:x}", u32::from(c))),
+        }
+    }
+    if identifier.is_empty() || identifier.starts_with(|c: char| c.is_ascii_digit()) {
+        identifier.insert(0, '_');
+    }
+    if RESERVED_WORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// Identifiers declared by a generated server, handing out a distinct one for every alias.
+#[derive(Debug)]
+pub struct Identifiers {
+    declared: HashSet<String>,
+}
+
+impl Identifiers {
+    pub fn new() -> Self {
+        Self {
+            declared: TEMPLATE_NAMES.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    /// Declare a valid identifier derived from `raw`, suffixed with a counter when an
+    /// earlier declaration already took it, e.g. for the `user-home` and `user_home`
+    /// directories.
+    pub fn declare(&mut self, raw: &str) -> String {
+        let base = to_identifier(raw);
+        let mut identifier = base.clone();
+        let mut counter = 2;
+        while !self.declared.insert(identifier.clone()) {
+            identifier = format!("{}_{}", base, counter);
+            counter += 1;
+        }
+        if identifier != raw {
+            log::debug!("Declared identifier {} for {}", identifier, raw);
+        }
+        identifier
+    }
+}
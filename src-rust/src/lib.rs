@@ -0,0 +1,1496 @@
+//! Compiler of next-express apps: scans the `app` directory of a source tree, analyzes the
+//! exports of its routes and generates the Express server registering them.
+//!
+//! [`compile`] runs the whole pipeline like the CLI does. Tools embedding the compiler can run
+//! the steps on their own: [`scan`], [`analyze`], then [`compile_to_string`] or
+//! [`manifest::build_manifest`].
+//!
+//! Every step reads and writes through a [`vfs::FileSystem`]: [`vfs::DiskFs`] for the real
+//! disk, or [`vfs::MemoryFs`] to compile sources that only exist in memory.
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub mod cache;
+mod conflicts;
+pub mod diagnostics;
+mod exports;
+mod identifiers;
+pub mod manifest;
+mod methods;
+pub mod openapi;
+pub mod project_config;
+mod route_config;
+pub mod routes;
+pub mod vfs;
+#[cfg(not(target_family = "wasm"))]
+pub mod watch;
+
+use cache::{ExportsCache, hash_source};
+use diagnostics::{Code, Diagnostic, Severity};
+use exports::parse_route_exports;
+pub use exports::{EndpointHandler, RouteExports};
+use identifiers::Identifiers;
+use methods::{ExportKind, FALLBACK_EXPORT, allowed_methods, classify_export};
+pub use route_config::{BodyLimit, RouteConfig};
+use vfs::FileSystem;
+
+/// Default server template, `/* __nextExpress_*__ */` placeholders are replaced by the
+/// generated code.
+pub const SERVER_TEMPLATE: &str = r#"import express from "express";
+/* __nextExpress_imports__ */
+
+export const createServer = () => {
+  const app = express();
+
+  /* __nextExpress_settings__ */
+
+  /* __nextExpress_topLevelMiddlewares__ */
+
+  /* __nextExpress_routes__ */
+
+  /* __nextExpress_tailMiddlewares__ */
+  return app;
+};
+"#;
+
+/// Default server template of the `commonjs` import style.
+pub const COMMONJS_SERVER_TEMPLATE: &str = r#"const express = require("express");
+/* __nextExpress_imports__ */
+
+const createServer = () => {
+  const app = express();
+
+  /* __nextExpress_settings__ */
+
+  /* __nextExpress_topLevelMiddlewares__ */
+
+  /* __nextExpress_routes__ */
+
+  /* __nextExpress_tailMiddlewares__ */
+  return app;
+};
+
+module.exports = { createServer };
+"#;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SubRouter {
+    identifier: String,
+    path: String,
+}
+
+/// A directory of the app tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppRoute {
+    /// Directory name, e.g. `[id]`
+    pub name: String,
+    /// Path relative to the source directory, e.g. `app/users/[id]`
+    pub relative_path: String,
+    /// Route file name, e.g. `route.ts`
+    pub route: Option<String>,
+    /// Middlewares file name, e.g. `middlewares.ts`
+    pub middlewares: Option<String>,
+    pub children: Vec<AppRoute>,
+
+    // runtime properties
+    pub(crate) sub_router: Option<SubRouter>,
+    /// Handlers and middlewares of the route file, filled in by [`analyze`]
+    pub exports: RouteExports,
+    /// Statically evaluated `config` export of the route file, filled in by [`analyze`]
+    pub route_config: RouteConfig,
+}
+
+impl AppRoute {
+    /// The route file, unless its config disables it.
+    pub fn enabled_route(&self) -> Option<&String> {
+        self.route.as_ref().filter(|_| !self.route_config.disabled)
+    }
+}
+
+/// A scanned source directory. Paths of files are relative to `src_dir`.
+#[derive(Debug, Clone)]
+pub struct AppStruct {
+    pub src_dir: String,
+    pub(crate) dist_to_src_relpath: String,
+    pub app: AppRoute,
+    pub top_level_middlewares: Option<String>,
+    pub tail_middlewares: Option<String>,
+    pub settings: Option<String>,
+    /// Problems [`analyze`] found that do not fail the compilation, in traversal order
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// File names, export names and server template the compiler looks for.
+#[derive(Debug, Clone)]
+pub struct Convention {
+    // template
+    pub server_template: String,
+
+    // routes
+    pub app_dir_name: String,
+    pub support_ext: Vec<String>,
+    pub route_file_basename: String,
+    pub middlewares_file_basename: String,
+    pub tail_middlewares_file_basename: String,
+    pub settings_file_basename: String,
+    pub custom_server_basename: String,
+
+    // exports
+    pub settings_export_name: String,
+    pub middlewares_export_name: String,
+    pub tail_middlewares_export_name: String,
+    pub openapi_export_name: String,
+    pub config_export_name: String,
+}
+
+impl Default for Convention {
+    fn default() -> Self {
+        Self {
+            server_template: SERVER_TEMPLATE.to_string(),
+
+            app_dir_name: "app".to_string(),
+            route_file_basename: "route".to_string(),
+            support_ext: [".ts", ".js", ".mts", ".cts", ".mjs", ".cjs", ".tsx", ".jsx"]
+                .map(String::from)
+                .to_vec(),
+            middlewares_file_basename: "middlewares".to_string(),
+            tail_middlewares_file_basename: "tail-middlewares".to_string(),
+            settings_file_basename: "settings".to_string(),
+            custom_server_basename: "custom-server".to_string(),
+
+            settings_export_name: "settings".to_string(),
+            middlewares_export_name: "middlewares".to_string(),
+            tail_middlewares_export_name: "middlewares".to_string(),
+            openapi_export_name: "openapi".to_string(),
+            config_export_name: "config".to_string(),
+        }
+    }
+}
+
+impl Convention {
+    /// Switch the default server template to the one of `import_style`, a custom template is
+    /// left as is.
+    pub fn apply_import_style(&mut self, import_style: ImportStyle) {
+        if import_style == ImportStyle::Commonjs && self.server_template == SERVER_TEMPLATE {
+            self.server_template = COMMONJS_SERVER_TEMPLATE.to_string();
+        }
+    }
+
+    fn get_filenames(&self, basename: &str) -> Vec<String> {
+        self.support_ext
+            .iter()
+            .map(|ext| format!("{}{}", basename, ext))
+            .collect()
+    }
+
+    fn get_middlewares_filenames(&self) -> Vec<String> {
+        self.get_filenames(&self.middlewares_file_basename)
+    }
+
+    fn get_tail_middlewares_filenames(&self) -> Vec<String> {
+        self.get_filenames(&self.tail_middlewares_file_basename)
+    }
+
+    fn get_settings_filenames(&self) -> Vec<String> {
+        self.get_filenames(&self.settings_file_basename)
+    }
+
+    fn get_route_filenames(&self) -> Vec<String> {
+        self.get_filenames(&self.route_file_basename)
+    }
+
+    fn get_custom_server_filenames(&self) -> Vec<String> {
+        self.get_filenames(&self.custom_server_basename)
+    }
+}
+
+/// How the handlers of a route are registered on its router.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RouteStyle {
+    /// A single `router.all` dispatching on `req.method`
+    Dispatch,
+    /// One `router.<method>` per handler, followed by a `router.all` answering
+    /// the remaining methods
+    Native,
+}
+
+/// How the generated server imports the source files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportStyle {
+    /// Extensionless ESM imports, resolved by a bundler such as tsup
+    Bundler,
+    /// ESM imports keeping the extension of every file, as Node ESM and `NodeNext`
+    /// resolution require
+    NodeEsm,
+    /// `require()` calls of extensionless paths, with a CommonJS default server template
+    Commonjs,
+}
+
+/// Options of the generated code.
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub route_style: RouteStyle,
+    pub import_style: ImportStyle,
+    pub method_not_allowed_res: String,
+    /// Response to an `OPTIONS` request of a route that does not export `OPTIONS`,
+    /// sent after the `Allow` header is set
+    pub options_res: String,
+    /// Response to a request still unanswered after the `timeoutMs` of its route config
+    pub timeout_res: String,
+    /// Fail instead of warning on exports that look like misnamed method handlers
+    pub strict: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            route_style: RouteStyle::Dispatch,
+            import_style: ImportStyle::Bundler,
+            method_not_allowed_res: "res.status(405).send(`Method ${req.method} Not Allowed`);"
+                .to_string(),
+            options_res: "res.status(204).end();".to_string(),
+            timeout_res: "res.status(503).send(\"Service Unavailable\");".to_string(),
+            strict: false,
+        }
+    }
+}
+
+/// A single directory segment of the app tree, classified by its naming convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteSegment<'a> {
+    /// `name`
+    Static(&'a str),
+    /// `(group)`, removed from the URL
+    VirtualGroup(&'a str),
+    /// `[param]` -> `:param`
+    Dynamic(&'a str),
+    /// `[...param]` -> `*param`, matches one or more segments
+    CatchAll(&'a str),
+    /// `[[...param]]` -> `{/*param}`, matches zero or more segments
+    OptionalCatchAll(&'a str),
+}
+
+impl<'a> RouteSegment<'a> {
+    fn parse(segment: &'a str) -> Self {
+        if let Some(param) = segment
+            .strip_prefix("[[...")
+            .and_then(|s| s.strip_suffix("]]"))
+        {
+            return Self::OptionalCatchAll(param);
+        }
+        if let Some(inner) = segment.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+            return match inner.strip_prefix("...") {
+                Some(param) => Self::CatchAll(param),
+                None => Self::Dynamic(inner),
+            };
+        }
+        if segment.starts_with('(') && segment.ends_with(')') {
+            return Self::VirtualGroup(segment);
+        }
+        Self::Static(segment)
+    }
+
+    /// Static segments must be registered before dynamic ones, and dynamic ones
+    /// before catch-alls, otherwise Express matches the wider pattern first.
+    fn order(&self) -> u8 {
+        match self {
+            Self::Static(_) | Self::VirtualGroup(_) => 0,
+            Self::Dynamic(_) => 1,
+            Self::CatchAll(_) => 2,
+            Self::OptionalCatchAll(_) => 3,
+        }
+    }
+
+    fn is_catch_all(&self) -> bool {
+        matches!(self, Self::CatchAll(_) | Self::OptionalCatchAll(_))
+    }
+
+    /// Express 5 path syntax for this segment, including the leading slash.
+    fn to_express_path(self) -> String {
+        match self {
+            Self::Static(name) => format!("/{}", name),
+            Self::VirtualGroup(_) => String::new(),
+            Self::Dynamic(param) => format!("/:{}", param),
+            Self::CatchAll(param) => format!("/*{}", param),
+            Self::OptionalCatchAll(param) => format!("{{/*{}}}", param),
+        }
+    }
+
+    fn display(self) -> String {
+        match self {
+            Self::Static(name) | Self::VirtualGroup(name) => name.to_string(),
+            Self::Dynamic(param) => format!(":{}", param),
+            Self::CatchAll(param) => format!("*{}", param),
+            Self::OptionalCatchAll(param) => format!("{{*{}}}", param),
+        }
+    }
+}
+
+fn route_name_to_identifier(name: &str) -> Result<String> {
+    let name = name.trim();
+    match RouteSegment::parse(name) {
+        RouteSegment::VirtualGroup(_) => {
+            anyhow::bail!("Virtual group should not be used as a route name")
+        }
+        // Handle dynamic route parameters [param], [...param], [[...param]] -> param
+        RouteSegment::Static(name)
+        | RouteSegment::Dynamic(name)
+        | RouteSegment::CatchAll(name)
+        | RouteSegment::OptionalCatchAll(name) => Ok(name.replace('-', "_")),
+    }
+}
+
+fn unique_route_handler_alias(app_route: &AppRoute) -> String {
+    app_route
+        .relative_path
+        .replace("...", "")
+        .replace(['/', '.', '-'], "_")
+        .replace(['(', ')', '[', ']'], "")
+}
+
+fn get_route_display_name(name: &str) -> String {
+    // Convert [param] to :param, [...param] to *param for display in comments
+    RouteSegment::parse(name).display()
+}
+
+fn get_route_display_path(relative_path: &str) -> String {
+    // Convert path segments [param] to :param for display in comments
+    relative_path
+        .split('/')
+        .map(|segment| RouteSegment::parse(segment).display())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Segments of a path relative to the source directory, without the leading app dir.
+fn app_relative_segments(relative_path: &str) -> impl Iterator<Item = &str> {
+    relative_path
+        .split_once('/')
+        .map_or("", |(_, path)| path)
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+}
+
+/// Convert a directory relative path to the path its sub router is mounted at,
+/// e.g. "app/manage/[org]" -> "/manage/:org".
+fn rel_dir_to_router_path(relative_path: &str) -> String {
+    let path = app_relative_segments(relative_path)
+        .map(|segment| RouteSegment::parse(segment).to_express_path())
+        .collect::<String>();
+    if path.is_empty() {
+        "/".to_string()
+    } else {
+        path
+    }
+}
+
+fn find_app_route_recursive_mut<'a>(
+    app_routes: &'a mut [AppRoute],
+    relative_path: &str,
+) -> Option<&'a mut AppRoute> {
+    for route in app_routes {
+        if route.relative_path == relative_path {
+            return Some(route);
+        }
+        if let Some(child_route) = find_app_route_recursive_mut(&mut route.children, relative_path)
+        {
+            return Some(child_route);
+        }
+    }
+    None
+}
+
+/// Canonicalize a dist directory that may not exist yet, e.g. when routes are only listed: its
+/// nearest existing ancestor is canonicalized and the missing components appended.
+fn canonicalize_dist_dir(fs: &dyn FileSystem, dist_dir: &Path) -> std::io::Result<PathBuf> {
+    let mut missing = Vec::new();
+    let mut path = dist_dir;
+    loop {
+        let existing = if path.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            path
+        };
+        match fs.canonicalize(existing) {
+            Ok(canonical) => {
+                return Ok(missing
+                    .into_iter()
+                    .rev()
+                    .fold(canonical, |path, name| path.join(name)));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                match (path.parent(), path.file_name()) {
+                    (Some(parent), Some(name)) => {
+                        missing.push(name);
+                        path = parent;
+                    }
+                    _ => return Err(e),
+                }
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+fn get_app_struct(
+    fs: &dyn FileSystem,
+    src_dir: &str,
+    dist_dir: &str,
+    convention: &Convention,
+) -> Result<AppStruct> {
+    log::debug!(
+        "Starting to analyze app structure from src_dir: {}",
+        src_dir
+    );
+
+    let src_path = fs.canonicalize(Path::new(src_dir)).map_err(|e| {
+        anyhow::anyhow!("Failed to canonicalize source directory {}: {}", src_dir, e)
+    })?;
+    let dist_path = canonicalize_dist_dir(fs, Path::new(dist_dir)).map_err(|e| {
+        anyhow::anyhow!("Failed to canonicalize dist directory {}: {}", dist_dir, e)
+    })?;
+
+    log::debug!(
+        "Canonicalized paths - src: {}, dist: {}",
+        src_path.display(),
+        dist_path.display()
+    );
+    let mut app_struct = AppStruct {
+        src_dir: src_dir.to_string(),
+        dist_to_src_relpath: pathdiff::diff_paths(&src_path, &dist_path)
+            .context("Failed to compute dist to src relative path")?
+            .to_string_lossy()
+            .to_string(),
+        app: AppRoute {
+            name: convention.app_dir_name.clone(),
+            relative_path: convention.app_dir_name.clone(),
+            route: None,
+            middlewares: None,
+            children: Vec::new(),
+            sub_router: None,
+            exports: RouteExports::default(),
+            route_config: RouteConfig::default(),
+        },
+        top_level_middlewares: None,
+        tail_middlewares: None,
+        settings: None,
+        warnings: Vec::new(),
+    };
+
+    log::info!("Scanning directory structure in: {}", src_path.display());
+    let mut file_count = 0;
+    let mut dir_count = 0;
+
+    let entries = fs
+        .walk(&src_path)
+        .with_context(|| format!("Failed to scan {}", src_path.display()))?;
+    for entry in entries {
+        let path = entry.path.as_path();
+        let relative_path =
+            pathdiff::diff_paths(path, &src_path).context("Failed to compute relative path")?;
+        let relative_path_str = relative_path.to_string_lossy().to_string();
+
+        let is_inside_app_dir = relative_path_str.starts_with(&convention.app_dir_name);
+        let parent_path = path.parent().unwrap_or(&src_path);
+        let parent_relative_path = pathdiff::diff_paths(parent_path, &src_path)
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let is_direct_children_of_app_dir = parent_path == src_path.join(&convention.app_dir_name);
+
+        if entry.is_dir {
+            dir_count += 1;
+            log::debug!("Found directory: {}", relative_path_str);
+
+            if is_direct_children_of_app_dir {
+                app_struct.app.children.push(AppRoute {
+                    name: entry.file_name(),
+                    relative_path: relative_path_str,
+                    route: None,
+                    middlewares: None,
+                    children: Vec::new(),
+                    sub_router: None,
+                    exports: RouteExports::default(),
+                    route_config: RouteConfig::default(),
+                });
+            } else if is_inside_app_dir
+                && let Some(app_route) = find_app_route_recursive_mut(
+                    &mut app_struct.app.children,
+                    &parent_relative_path,
+                )
+            {
+                app_route.children.push(AppRoute {
+                    name: entry.file_name(),
+                    relative_path: relative_path_str,
+                    route: None,
+                    middlewares: None,
+                    children: Vec::new(),
+                    sub_router: None,
+                    exports: RouteExports::default(),
+                    route_config: RouteConfig::default(),
+                });
+            }
+        } else {
+            file_count += 1;
+            log::debug!("Processing file: {}", relative_path_str);
+
+            // Handle files
+            let filename = entry.file_name();
+
+            // Handle top-level files
+            let middleware_names = convention.get_middlewares_filenames();
+            if middleware_names.contains(&relative_path_str) {
+                log::info!("Found top-level middleware file: {}", relative_path_str);
+                app_struct.top_level_middlewares = Some(relative_path_str.clone());
+            }
+
+            let tail_middlewares_names = convention.get_tail_middlewares_filenames();
+            if tail_middlewares_names.contains(&relative_path_str) {
+                log::info!("Found tail middleware file: {}", relative_path_str);
+                app_struct.tail_middlewares = Some(relative_path_str.clone());
+            }
+
+            let settings_names = convention.get_settings_filenames();
+            if settings_names.contains(&relative_path_str) {
+                log::info!("Found settings file: {}", relative_path_str);
+                app_struct.settings = Some(relative_path_str.clone());
+            }
+
+            // Handle route and middleware files
+            let route_names = convention.get_route_filenames();
+            let middleware_names = convention.get_middlewares_filenames();
+
+            if is_direct_children_of_app_dir {
+                if route_names.contains(&filename) {
+                    log::info!(
+                        "Found app-level route file: {}/{}",
+                        convention.app_dir_name,
+                        filename
+                    );
+                    set_route_file(&mut app_struct.app, &filename, path)?;
+                }
+                if middleware_names.contains(&filename) {
+                    log::info!(
+                        "Found app-level middleware file: {}/{}",
+                        convention.app_dir_name,
+                        filename
+                    );
+                    app_struct.app.middlewares = Some(filename.clone());
+                }
+            } else if is_inside_app_dir
+                && let Some(app_route) = find_app_route_recursive_mut(
+                    &mut app_struct.app.children,
+                    &parent_relative_path,
+                )
+            {
+                let is_virtual_group_dir =
+                    app_route.name.starts_with('(') && app_route.name.ends_with(')');
+                if !is_virtual_group_dir {
+                    if route_names.contains(&filename) {
+                        log::info!("Found route file: {}/{}", app_route.relative_path, filename);
+                        set_route_file(app_route, &filename, path)?;
+                    }
+                    if middleware_names.contains(&filename) {
+                        log::info!(
+                            "Found middleware file: {}/{}",
+                            app_route.relative_path,
+                            filename
+                        );
+                        app_route.middlewares = Some(filename.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "Directory scan completed: {} directories, {} files processed",
+        dir_count,
+        file_count
+    );
+    log::debug!("App structure analysis completed");
+
+    Ok(app_struct)
+}
+
+/// Record the route file of a directory, which may only have one, e.g. not both `route.ts`
+/// and `route.js`.
+fn set_route_file(app_route: &mut AppRoute, filename: &str, path: &Path) -> Result<()> {
+    if let Some(existing) = &app_route.route {
+        return Err(Diagnostic::error(
+            Code::RouteConflict,
+            format!(
+                "Conflicting route files {} and {}, a directory can only have one",
+                existing, filename
+            ),
+        )
+        .with_file(path)
+        .into());
+    }
+    app_route.route = Some(filename.to_string());
+    Ok(())
+}
+
+impl ImportStyle {
+    /// Import specifier of a source file as the emitted server sees it.
+    fn specifier(self, file_path: &str) -> String {
+        if self == ImportStyle::NodeEsm {
+            return file_path.to_string();
+        }
+        let Some((stem, ext)) = file_path.rsplit_once('.') else {
+            return file_path.to_string();
+        };
+        // `.mts` and `.cts` files are imported through the `.mjs` and `.cjs` files they
+        // compile to, the other extensions are left to the resolver
+        match ext {
+            "ts" | "tsx" | "js" | "jsx" => stem.to_string(),
+            "mts" => format!("{}.mjs", stem),
+            "cts" => format!("{}.cjs", stem),
+            _ => file_path.to_string(),
+        }
+    }
+
+    /// Statement binding the `export_name` export of `specifier` to `alias`.
+    fn import_statement(self, export_name: &str, alias: &str, specifier: &str) -> String {
+        match self {
+            ImportStyle::Bundler | ImportStyle::NodeEsm => format!(
+                "import {{ {} as {} }} from \"{}\";\n",
+                export_name, alias, specifier
+            ),
+            ImportStyle::Commonjs => format!(
+                "const {{ {}: {} }} = require(\"{}\");\n",
+                export_name, alias, specifier
+            ),
+        }
+    }
+}
+
+/// Convert the path of a route file relative to the source directory to its endpoint,
+/// e.g. "app/todos/[id]/route.ts" -> "/todos/:id/".
+fn rel_path_to_endpoint(rel_path: &str) -> Result<String> {
+    // The first segment is the app dir and the last one the route file
+    let Some((dir_path, _)) = rel_path.rsplit_once('/') else {
+        anyhow::bail!("Invalid route path: {}", rel_path);
+    };
+    let mut endpoint = String::new();
+    let mut ends_with_catch_all = false;
+
+    for segment in app_relative_segments(dir_path) {
+        let segment = RouteSegment::parse(segment);
+        if matches!(segment, RouteSegment::VirtualGroup(_)) {
+            continue;
+        }
+        if ends_with_catch_all {
+            anyhow::bail!(
+                "Catch-all segment must be the last segment of a route: {}",
+                rel_path
+            );
+        }
+        ends_with_catch_all = segment.is_catch_all();
+        // Convert [param] to :param, [...param] to *param and [[...param]] to {/*param}
+        endpoint.push_str(&segment.to_express_path());
+    }
+    // Catch-all patterns consume the rest of the path, a trailing slash
+    // would make the last matched segment mandatory
+    if !ends_with_catch_all {
+        endpoint.push('/');
+    }
+
+    Ok(endpoint)
+}
+
+/// Keep only the exports named after an HTTP method and the fallback `default` export,
+/// reporting the ones that look like a misnamed handler and per-method middlewares without
+/// a handler for their method. Those are errors in strict mode, pushed to `warnings` otherwise.
+fn retain_method_handlers(
+    route_abs_path: &Path,
+    route_exports: &mut RouteExports,
+    convention: &Convention,
+    config: &Config,
+    warnings: &mut Vec<Diagnostic>,
+) -> Result<()> {
+    let mut problems = Vec::new();
+    route_exports.handlers.retain(|handler| {
+        let name = &handler.export_name;
+        match classify_export(name) {
+            ExportKind::Method | ExportKind::Fallback => return true,
+            ExportKind::Miscased(method) => problems.push(
+                Diagnostic::error(
+                    Code::BadExport,
+                    format!(
+                        "`{}` is not a handler, HTTP method exports must be uppercase",
+                        name
+                    ),
+                )
+                .with_position(handler.position)
+                .with_note(format!("rename the export to `{}`", method)),
+            ),
+            ExportKind::Misspelled(method) => problems.push(
+                Diagnostic::error(Code::BadExport, format!("`{}` is not an HTTP method", name))
+                    .with_position(handler.position)
+                    .with_note(format!("did you mean `{}`?", method)),
+            ),
+            ExportKind::Other => log::debug!(
+                "Skipping export {} of {}, not an HTTP method",
+                name,
+                route_abs_path.display()
+            ),
+        }
+        false
+    });
+    let handlers = &route_exports.handlers;
+    route_exports.middlewares.retain(|middlewares| {
+        let Some(method) = route_middlewares_method(middlewares, convention) else {
+            return true;
+        };
+        if handlers.iter().any(|handler| handler.export_name == method) {
+            return true;
+        }
+        problems.push(Diagnostic::error(
+            Code::BadExport,
+            format!(
+                "`{}` has no matching `{}` handler export",
+                middlewares, method
+            ),
+        ));
+        false
+    });
+
+    let problems = problems
+        .into_iter()
+        .map(|problem| problem.with_file(route_abs_path));
+    if config.strict {
+        return diagnostics::check(problems.map(anyhow::Error::from).collect());
+    }
+    warnings.extend(problems.map(|problem| Diagnostic {
+        severity: Severity::Warning,
+        ..problem
+    }));
+    Ok(())
+}
+
+/// The method of a `<METHOD>_middlewares` export, `None` for the route-wide `middlewares`.
+pub(crate) fn route_middlewares_method<'a>(
+    name: &'a str,
+    convention: &Convention,
+) -> Option<&'a str> {
+    name.strip_suffix(&convention.middlewares_export_name)
+        .and_then(|prefix| prefix.strip_suffix('_'))
+}
+
+/// Route files of the tree relative to the source directory, in traversal order.
+fn collect_route_files(app_route: &AppRoute, route_files: &mut Vec<String>) {
+    if let Some(route) = &app_route.route {
+        route_files.push(format!("{}/{}", app_route.relative_path, route));
+    }
+    for child in &app_route.children {
+        collect_route_files(child, route_files);
+    }
+}
+
+fn assign_route_exports(
+    app_route: &mut AppRoute,
+    exports_by_route: &mut HashMap<String, (RouteExports, RouteConfig)>,
+) {
+    if let Some(route) = &app_route.route
+        && let Some((route_exports, route_config)) =
+            exports_by_route.remove(&format!("{}/{}", app_route.relative_path, route))
+    {
+        app_route.exports = route_exports;
+        app_route.route_config = route_config;
+    }
+    for child in &mut app_route.children {
+        assign_route_exports(child, exports_by_route);
+    }
+}
+
+/// Parse the exports of every route file in parallel, before any code is emitted.
+///
+/// Results are applied in traversal order, so errors, warnings and output stay deterministic.
+fn analyze_app_struct(
+    fs: &dyn FileSystem,
+    app_struct: &mut AppStruct,
+    convention: &Convention,
+    config: &Config,
+    cache: &mut ExportsCache,
+) -> Result<()> {
+    let mut route_files = Vec::new();
+    collect_route_files(&app_struct.app, &mut route_files);
+    log::debug!("Analyzing {} route files", route_files.len());
+
+    let src_path = PathBuf::from(&app_struct.src_dir);
+    let cached = &*cache;
+    let results = route_files
+        .par_iter()
+        .map(|route_rel_path| -> Result<(String, RouteExports)> {
+            let route_abs_path = src_path.join(route_rel_path);
+            let source = fs
+                .read_to_string(&route_abs_path)
+                .with_context(|| format!("Failed to read {}", route_abs_path.display()))?;
+            let hash = hash_source(&source);
+
+            if let Some(route_exports) = cached.get(fs, route_rel_path, &hash) {
+                log::debug!("Using cached exports for: {}", route_rel_path);
+                return Ok((hash, route_exports));
+            }
+            let route_exports = parse_route_exports(fs, &route_abs_path, source, convention)?;
+            Ok((hash, route_exports))
+        })
+        .collect::<Vec<_>>();
+
+    // Every broken route is reported at once, not only the first one
+    let mut errors = Vec::new();
+    let warnings = &mut app_struct.warnings;
+    warnings.clear();
+    let mut exports_by_route = HashMap::new();
+    for (route_rel_path, result) in route_files.into_iter().zip(results) {
+        let (hash, mut route_exports) = match result {
+            Ok(analyzed) => analyzed,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        // Cached as analyzed, warnings included, once the route is known to compile
+        let analyzed = route_exports.clone();
+        // Ignored exports fail the compilation in strict mode, like misnamed handlers
+        for warning in std::mem::take(&mut route_exports.warnings) {
+            match warning.code {
+                Some(Code::BadExport | Code::InvalidOpenApi) if config.strict => {
+                    errors.push(
+                        Diagnostic {
+                            severity: Severity::Error,
+                            ..warning
+                        }
+                        .into(),
+                    );
+                }
+                _ => warnings.push(warning),
+            }
+        }
+        let route_abs_path = src_path.join(&route_rel_path);
+        let route_config = match RouteConfig::parse(route_exports.config.as_ref(), &route_abs_path)
+        {
+            Ok(route_config) => route_config,
+            Err(e) => {
+                errors.push(e);
+                continue;
+            }
+        };
+        if route_config.disabled {
+            log::info!("Skipping disabled route {}", route_abs_path.display());
+            cache.insert(fs, &route_rel_path, hash, analyzed);
+            exports_by_route.insert(route_rel_path, (route_exports, route_config));
+            continue;
+        }
+        if let Err(e) = retain_method_handlers(
+            &route_abs_path,
+            &mut route_exports,
+            convention,
+            config,
+            warnings,
+        ) {
+            errors.push(e);
+            continue;
+        }
+        // Handlers left out of the configured methods are not routed, nor are their middlewares
+        route_exports
+            .handlers
+            .retain(|handler| route_config.routes_method(&handler.export_name));
+        route_exports.middlewares.retain(|middlewares| {
+            route_middlewares_method(middlewares, convention)
+                .is_none_or(|method| route_config.routes_method(method))
+        });
+        if route_exports.handlers.is_empty() {
+            errors.push(
+                Diagnostic::error(Code::EmptyRoute, "No valid endpoint handlers found")
+                    .with_file(route_abs_path)
+                    .with_note(
+                        "export a function named after an HTTP method, \
+                         e.g. `export async function GET(req, res)`, or a default export",
+                    )
+                    .into(),
+            );
+            continue;
+        }
+        cache.insert(fs, &route_rel_path, hash, analyzed);
+        exports_by_route.insert(route_rel_path, (route_exports, route_config));
+    }
+    diagnostics::check(errors)?;
+
+    assign_route_exports(&mut app_struct.app, &mut exports_by_route);
+    Ok(())
+}
+
+/// Wrap the body of a generated route handler, forwarding thrown and rejected errors
+/// to the error middlewares.
+fn endpoint_wrapper(body: &str) -> String {
+    format!(
+        "async (req, res, next) => {{ try {{ {} }} catch (err) {{ next(err); }} }}",
+        body
+    )
+}
+
+/// Code generated for the routes, along with the identifiers it declares.
+struct RoutesCode {
+    imports: String,
+    routes: String,
+    identifiers: Identifiers,
+}
+
+fn compile_route(
+    code: &mut RoutesCode,
+    app_route: &mut AppRoute,
+    dist_to_src_relpath: &str,
+    nearest_sub_router: Option<&SubRouter>,
+    convention: &Convention,
+    config: &Config,
+) -> Result<()> {
+    log::debug!(
+        "Compiling route: {} at {}",
+        app_route.name,
+        app_route.relative_path
+    );
+
+    let display_name = get_route_display_name(&app_route.name);
+    let display_path = get_route_display_path(&app_route.relative_path);
+    code.routes.push_str(&format!(
+        "// ===== routes [{} | {}] =====\n",
+        display_name, display_path
+    ));
+
+    let mut current_nearest_sub_router = nearest_sub_router;
+
+    if let Some(_middlewares) = &app_route.middlewares {
+        log::debug!("Setting up middleware router for: {}", app_route.name);
+        // Calculate the full path from app root by converting relative_path to endpoint
+        // e.g., "app/manage/admin" -> "/manage/admin", "app/org/[id]" -> "/org/:id"
+        let full_router_path = rel_dir_to_router_path(&app_route.relative_path);
+        // If there's a parent sub-router, we need to make this path relative to it
+        let group_route_path = if let Some(sub_router) = nearest_sub_router {
+            full_router_path.replace(&sub_router.path, "")
+        } else {
+            full_router_path.clone()
+        };
+        let route_identifier = route_name_to_identifier(&app_route.name)?;
+        let route_middlewares_alias = code
+            .identifiers
+            .declare(&format!("{}Middlewares", route_identifier));
+
+        code.imports.push_str(&config.import_style.import_statement(
+            &convention.middlewares_export_name,
+            &route_middlewares_alias,
+            &format!(
+                "{}/{}/{}",
+                dist_to_src_relpath,
+                app_route.relative_path,
+                config
+                    .import_style
+                    .specifier(app_route.middlewares.as_ref().unwrap())
+            ),
+        ));
+
+        let group_router_identifier = code
+            .identifiers
+            .declare(&format!("{}Router", route_identifier));
+        // Routers mounted below a dynamic path must see the parent's params
+        let router_options = if full_router_path.contains([':', '*']) {
+            "{ mergeParams: true }"
+        } else {
+            ""
+        };
+        // Store the full path from app root for child routes to use
+        app_route.sub_router = Some(SubRouter {
+            identifier: group_router_identifier.clone(),
+            path: full_router_path,
+        });
+        // Use the parent router if available, otherwise use app
+        let parent_router = nearest_sub_router
+            .map(|s| s.identifier.as_str())
+            .unwrap_or("app");
+        current_nearest_sub_router = app_route.sub_router.as_ref();
+
+        code.routes.push_str(&format!(
+            "const {} = express.Router({});\n",
+            group_router_identifier, router_options
+        ));
+        code.routes.push_str(&format!(
+            "{}.use(\"{}\", {});\n",
+            parent_router, group_route_path, group_router_identifier
+        ));
+        code.routes.push_str(&format!(
+            "{}.use(...{});\n",
+            group_router_identifier, route_middlewares_alias
+        ));
+    }
+
+    if let Some(route) = app_route.enabled_route() {
+        log::debug!("Processing route handlers for: {}", app_route.name);
+
+        let mut endpoint_uri =
+            rel_path_to_endpoint(&format!("{}/{}", app_route.relative_path, route))?;
+        if let Some(sub_router) = current_nearest_sub_router {
+            endpoint_uri = endpoint_uri.replace(&sub_router.path, "");
+        }
+
+        log::debug!(
+            "Mapped endpoint URI: {} for route: {}",
+            endpoint_uri,
+            app_route.name
+        );
+
+        let router = current_nearest_sub_router
+            .map(|s| s.identifier.as_str())
+            .unwrap_or("app");
+        let is_native = config.route_style == RouteStyle::Native;
+        let route_import_path = format!(
+            "{}/{}/{}",
+            dist_to_src_relpath,
+            app_route.relative_path,
+            config.import_style.specifier(route)
+        );
+
+        // Middlewares exported by the route file only guard this endpoint,
+        // the route-wide ones run before the per-method ones
+        let mut method_middlewares = HashMap::new();
+        for middlewares in &app_route.exports.middlewares {
+            let middlewares_alias = code.identifiers.declare(&format!(
+                "{}_{}",
+                unique_route_handler_alias(app_route),
+                middlewares
+            ));
+            code.imports.push_str(&config.import_style.import_statement(
+                middlewares,
+                &middlewares_alias,
+                &route_import_path,
+            ));
+            match route_middlewares_method(middlewares, convention) {
+                Some(method) => {
+                    method_middlewares.insert(method, middlewares_alias);
+                }
+                None => code.routes.push_str(&format!(
+                    "{}.all(\"{}\", ...{});\n",
+                    router, endpoint_uri, middlewares_alias
+                )),
+            }
+        }
+
+        let mut endpoint_handler_inner = String::new();
+        let mut fallback_handler = None;
+        let mut get_handler = None;
+        // Express routes HEAD requests to `router.get` handlers and middlewares, a HEAD
+        // handler is only reached when registered first
+        let mut handlers: Vec<_> = app_route.exports.handlers.iter().collect();
+        handlers.sort_by_key(|handler| handler.export_name != "HEAD");
+        for handler in handlers {
+            let handler_alias = code.identifiers.declare(&format!(
+                "{}_{}",
+                unique_route_handler_alias(app_route),
+                handler.export_name
+            ));
+            code.imports.push_str(&config.import_style.import_statement(
+                &handler.export_name,
+                &handler_alias,
+                &route_import_path,
+            ));
+            // Await every handler, a sync one may still return a promise
+            let handler_call = format!("await {}(req, res, next);", handler_alias);
+            if handler.export_name == FALLBACK_EXPORT {
+                fallback_handler = Some(handler_call);
+                continue;
+            }
+            if handler.export_name == "GET" {
+                get_handler = Some(handler_call.clone());
+            }
+            let middlewares_alias = method_middlewares.get(handler.export_name.as_str());
+            if is_native {
+                code.routes.push_str(&format!(
+                    "{}.{}(\"{}\", {}{});\n",
+                    router,
+                    handler.export_name.to_lowercase(),
+                    endpoint_uri,
+                    middlewares_alias
+                        .map(|alias| format!("...{}, ", alias))
+                        .unwrap_or_default(),
+                    endpoint_wrapper(&handler_call)
+                ));
+                continue;
+            }
+            // The dispatcher below is reached through `next()` once they pass
+            if let Some(middlewares_alias) = middlewares_alias {
+                code.routes.push_str(&format!(
+                    "{}.{}(\"{}\", ...{});\n",
+                    router,
+                    handler.export_name.to_lowercase(),
+                    endpoint_uri,
+                    middlewares_alias
+                ));
+            }
+            endpoint_handler_inner.push_str(&format!(
+                "if (req.method === \"{}\") {{ {} return; }}\n",
+                handler.export_name.to_uppercase(),
+                handler_call
+            ));
+        }
+
+        // The default export handles every method without its own handler,
+        // otherwise HEAD and OPTIONS are answered automatically
+        let unhandled_method_res = match fallback_handler {
+            Some(fallback_handler) => fallback_handler,
+            None => {
+                let allow_header = format!(
+                    "res.set(\"Allow\", \"{}\");",
+                    allowed_methods(&app_route.exports).join(", ")
+                );
+                let has_handler = |method: &str| {
+                    app_route
+                        .exports
+                        .handlers
+                        .iter()
+                        .any(|handler| handler.export_name == method)
+                };
+                // Express already routes HEAD requests to `router.get` handlers
+                if let Some(get_handler) = &get_handler
+                    && !has_handler("HEAD")
+                    && !is_native
+                {
+                    endpoint_handler_inner.push_str(&format!(
+                        "if (req.method === \"HEAD\") {{ {} return; }}\n",
+                        get_handler
+                    ));
+                }
+                if !has_handler("OPTIONS") {
+                    endpoint_handler_inner.push_str(&format!(
+                        "if (req.method === \"OPTIONS\") {{ {} {} return; }}\n",
+                        allow_header, config.options_res
+                    ));
+                }
+                format!("{} {}", allow_header, config.method_not_allowed_res)
+            }
+        };
+        let endpoint_handler = format!(
+            "{}.all(\"{}\", {});\n",
+            router,
+            endpoint_uri,
+            endpoint_wrapper(&format!(
+                "{} {}",
+                endpoint_handler_inner, unhandled_method_res
+            )),
+        );
+        code.routes.push_str(&endpoint_handler);
+    }
+
+    code.routes.push('\n');
+    Ok(())
+}
+
+#[derive(Debug)]
+struct CompiledAppStruct {
+    imports: String,
+    settings: String,
+    top_level_middlewares: String,
+    routes: String,
+    tail_middlewares: String,
+}
+
+fn compile_app_struct(
+    app_struct: &mut AppStruct,
+    convention: &Convention,
+    config: &Config,
+) -> Result<CompiledAppStruct> {
+    log::info!("Starting app structure compilation");
+
+    let mut imports = String::new();
+    let mut settings = String::new();
+    let mut top_level_middlewares = String::new();
+    let mut tail_middlewares = String::new();
+
+    if let Some(settings_file) = &app_struct.settings {
+        log::debug!("Adding settings import from: {}", settings_file);
+        imports.push_str(&config.import_style.import_statement(
+            &convention.settings_export_name,
+            "appSettings",
+            &format!(
+                "{}/{}",
+                app_struct.dist_to_src_relpath,
+                config.import_style.specifier(settings_file)
+            ),
+        ));
+        settings.push_str("for (const setting of appSettings) {\n      app.set(setting.name, setting.value);\n    }\n");
+    }
+
+    // Route configs apply before the top-level middlewares, so that e.g. a route body limit
+    // is not preempted by a global body parser. Route timeouts thus also cover those middlewares.
+    fn compile_route_config(
+        app_route: &AppRoute,
+        top_level_middlewares: &mut String,
+        config: &Config,
+    ) -> Result<()> {
+        if let Some(route) = app_route.enabled_route() {
+            let middlewares = app_route.route_config.middlewares(&config.timeout_res);
+            if !middlewares.is_empty() {
+                top_level_middlewares.push_str(&format!(
+                    "app.all(\"{}\", {});\n",
+                    rel_path_to_endpoint(&format!("{}/{}", app_route.relative_path, route))?,
+                    middlewares.join(", ")
+                ));
+            }
+        }
+        for child in &app_route.children {
+            compile_route_config(child, top_level_middlewares, config)?;
+        }
+        Ok(())
+    }
+    compile_route_config(&app_struct.app, &mut top_level_middlewares, config)?;
+
+    if let Some(middlewares_file) = &app_struct.top_level_middlewares {
+        log::debug!(
+            "Adding top-level middlewares import from: {}",
+            middlewares_file
+        );
+        imports.push_str(&config.import_style.import_statement(
+            &convention.middlewares_export_name,
+            "topLevelMiddlewares",
+            &format!(
+                "{}/{}",
+                app_struct.dist_to_src_relpath,
+                config.import_style.specifier(middlewares_file)
+            ),
+        ));
+        top_level_middlewares.push_str("app.use(...topLevelMiddlewares);\n");
+    }
+
+    if let Some(tail_middlewares_file) = &app_struct.tail_middlewares {
+        log::debug!(
+            "Adding tail-middlewares handler import from: {}",
+            tail_middlewares_file
+        );
+        imports.push_str(&config.import_style.import_statement(
+            &convention.tail_middlewares_export_name,
+            "tailMiddlewares",
+            &format!(
+                "{}/{}",
+                app_struct.dist_to_src_relpath,
+                config.import_style.specifier(tail_middlewares_file)
+            ),
+        ));
+        tail_middlewares.push_str("app.use(...tailMiddlewares);\n");
+    }
+
+    fn traverse_route(
+        app_route: &mut AppRoute,
+        code: &mut RoutesCode,
+        dist_to_src_relpath: &str,
+        nearest_sub_router: Option<&SubRouter>,
+        convention: &Convention,
+        config: &Config,
+    ) -> Result<()> {
+        log::debug!(
+            "Traversing route: {} (children: {})",
+            app_route.name,
+            app_route.children.len()
+        );
+
+        if app_route.enabled_route().is_some() || app_route.middlewares.is_some() {
+            compile_route(
+                code,
+                app_route,
+                dist_to_src_relpath,
+                nearest_sub_router,
+                convention,
+                config,
+            )?;
+        }
+
+        let current_sub_router = app_route.sub_router.as_ref().or(nearest_sub_router);
+
+        for child in &mut app_route.children {
+            traverse_route(
+                child,
+                code,
+                dist_to_src_relpath,
+                current_sub_router,
+                convention,
+                config,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    log::info!("Traversing application routes");
+    let mut code = RoutesCode {
+        imports,
+        routes: String::new(),
+        identifiers: Identifiers::new(),
+    };
+    traverse_route(
+        &mut app_struct.app,
+        &mut code,
+        &app_struct.dist_to_src_relpath,
+        None,
+        convention,
+        config,
+    )?;
+    let RoutesCode {
+        imports, routes, ..
+    } = code;
+
+    log::info!("App structure compilation completed");
+
+    Ok(CompiledAppStruct {
+        imports,
+        settings,
+        top_level_middlewares,
+        routes,
+        tail_middlewares,
+    })
+}
+
+// for test case, sort app route to match ts-impl
+// static segments come first so that they are not shadowed by dynamic siblings
+fn sort_app_route(app_struct: &mut AppRoute) {
+    app_struct
+        .children
+        .sort_by_key(|a| (RouteSegment::parse(&a.name).order(), a.name.to_lowercase()));
+    if app_struct.children.len() > 1 {
+        for child in &mut app_struct.children {
+            sort_app_route(child);
+        }
+    }
+}
+
+/// Scan the source directory into its app structure, with the routes sorted in the order the
+/// server registers them. The exports of the routes are left to [`analyze`].
+pub fn scan(
+    fs: &dyn FileSystem,
+    src_dir: &str,
+    dist_dir: &str,
+    convention: &Convention,
+) -> Result<AppStruct> {
+    log::info!("Building app structure");
+    let mut app_struct = get_app_struct(fs, src_dir, dist_dir, convention)?;
+
+    log::debug!("Sorting app routes for consistent output");
+    sort_app_route(&mut app_struct.app);
+    Ok(app_struct)
+}
+
+/// Analyze the exports of every route of a scanned app structure, failing on routes the
+/// server could not expose. Failures are [`diagnostics::Diagnostic`]s or
+/// [`diagnostics::Diagnostics`] when they come from the sources, the latter starting with the
+/// warnings found so far. Otherwise the warnings are left in [`AppStruct::warnings`].
+pub fn analyze(
+    fs: &dyn FileSystem,
+    app_struct: &mut AppStruct,
+    convention: &Convention,
+    config: &Config,
+    cache: &mut ExportsCache,
+) -> Result<()> {
+    log::info!("Analyzing route exports");
+    analyze_app_struct(fs, app_struct, convention, config, cache)
+        .and_then(|()| cache.save(fs))
+        .and_then(|()| {
+            conflicts::check_route_conflicts(&app_struct.app, Path::new(&app_struct.src_dir))
+        })
+        .map_err(|e| diagnostics::with_warnings(std::mem::take(&mut app_struct.warnings), e))
+}
+
+/// Generate the server of an analyzed app structure, from the custom server template of the
+/// source directory if there is one.
+pub fn compile_to_string(
+    fs: &dyn FileSystem,
+    app_struct: &mut AppStruct,
+    convention: &Convention,
+    config: &Config,
+) -> Result<String> {
+    let mut server_template = None;
+    let custom_server_paths: Vec<PathBuf> = convention
+        .get_custom_server_filenames()
+        .iter()
+        .map(|f| PathBuf::from(&app_struct.src_dir).join(f))
+        .collect();
+    for custom_server_path in &custom_server_paths {
+        if fs.exists(custom_server_path) {
+            log::info!(
+                "Found custom server template at: {}",
+                custom_server_path.display()
+            );
+            server_template = Some(
+                fs.read_to_string(custom_server_path)
+                    .context("Failed to read custom server template")?,
+            );
+            break;
+        }
+    }
+    let server_template = server_template
+        .as_ref()
+        .unwrap_or(&convention.server_template);
+
+    log::info!("Compiling app structure to code");
+    let transformed = compile_app_struct(app_struct, convention, config)?;
+
+    log::debug!("Generating final output from template");
+    Ok(server_template
+        .replace("/* __nextExpress_imports__ */", &transformed.imports)
+        .replace("/* __nextExpress_settings__ */", &transformed.settings)
+        .replace(
+            "/* __nextExpress_topLevelMiddlewares__ */",
+            &transformed.top_level_middlewares,
+        )
+        .replace("/* __nextExpress_routes__ */", &transformed.routes)
+        .replace(
+            "/* __nextExpress_tailMiddlewares__ */",
+            &transformed.tail_middlewares,
+        ))
+}
+
+/// Scan, analyze and compile the server of `src_dir` to `<dist_dir>/<filename>`, leaving the
+/// file untouched when its content did not change.
+pub fn compile(
+    fs: &dyn FileSystem,
+    src_dir: &str,
+    dist_dir: &str,
+    filename: &str,
+    convention: &Convention,
+    config: &Config,
+    cache: &mut ExportsCache,
+) -> Result<AppStruct> {
+    log::info!("Starting compilation process");
+    log::debug!(
+        "Parameters - src_dir: {}, dist_dir: {}, filename: {}",
+        src_dir,
+        dist_dir,
+        filename
+    );
+
+    log::debug!("Ensuring output directory exists");
+    let output_path = Path::new(dist_dir).join(filename);
+    if let Some(parent) = output_path.parent() {
+        fs.create_dir_all(parent)?;
+    }
+
+    let mut app_struct = scan(fs, src_dir, dist_dir, convention)?;
+    analyze(fs, &mut app_struct, convention, config, cache)?;
+    let output = compile_to_string(fs, &mut app_struct, convention, config)?;
+
+    // Leave the file untouched when nothing changed, so downstream watchers do not rebuild
+    if fs
+        .read_to_string(&output_path)
+        .is_ok_and(|existing| existing == output)
+    {
+        log::info!("Output unchanged, skip writing: {}", output_path.display());
+    } else {
+        log::info!("Writing output to: {}", output_path.display());
+        fs.write(&output_path, &output)?;
+    }
+
+    log::info!("Compilation completed successfully");
+    Ok(app_struct)
+}
@@ -1,919 +1,208 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use clap::Parser as ClapParser;
-use serde::{Deserialize, Serialize};
-use std::fs;
-use std::path::{Path, PathBuf};
-use walkdir::WalkDir;
-
-use swc_common::SourceMap;
-use swc_common::sync::Lrc;
-use swc_ecma_ast::*;
-use swc_ecma_parser::{Parser, StringInput, Syntax, TsSyntax, lexer::Lexer};
-use swc_ecma_visit::{Visit, VisitWith};
-
-const SERVER_TEMPLATE: &str = r#"import express from "express";
-/* __nextExpress_imports__ */
-
-export const createServer = () => {
-  const app = express();
-
-  /* __nextExpress_settings__ */
-
-  /* __nextExpress_topLevelMiddlewares__ */
-
-  /* __nextExpress_routes__ */
-
-  /* __nextExpress_tailMiddlewares__ */
-  return app;
+use std::path::Path;
+use std::process::ExitCode;
+
+use nexp_compiler_rs::cache::ExportsCache;
+use nexp_compiler_rs::diagnostics::{Diagnostic, MessageFormat, Severity};
+use nexp_compiler_rs::project_config::ProjectConfig;
+use nexp_compiler_rs::routes::{self, RoutesFormat};
+use nexp_compiler_rs::vfs::{DiskFs, FileSystem};
+#[cfg(not(target_family = "wasm"))]
+use nexp_compiler_rs::watch;
+use nexp_compiler_rs::{
+    AppStruct, Config, Convention, ImportStyle, RouteStyle, analyze, compile, manifest, openapi,
+    scan,
 };
-"#;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct SubRouter {
-    identifier: String,
-    path: String,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct AppRoute {
-    name: String,
-    relative_path: String,
-    route: Option<String>,
-    middlewares: Option<String>,
-    children: Vec<AppRoute>,
-
-    // runtime properties
-    sub_router: Option<SubRouter>,
-}
+#[derive(ClapParser, Debug)]
+#[command(version, about = "A compiler cli for next-express writen in rust.", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
 
-#[derive(Debug, Clone)]
-struct AppStruct {
+    #[arg(long, global = true, default_value = "src")]
     src_dir: String,
-    dist_to_src_relpath: String,
-    app: AppRoute,
-    top_level_middlewares: Option<String>,
-    tail_middlewares: Option<String>,
-    settings: Option<String>,
-}
-
-#[derive(Debug, Clone)]
-struct Convention {
-    // template
-    server_template: String,
-
-    // routes
-    app_dir_name: String,
-    support_ext: Vec<String>,
-    route_file_basename: String,
-    middlewares_file_basename: String,
-    tail_middlewares_file_basename: String,
-    settings_file_basename: String,
-    custom_server_basename: String,
 
-    // exports
-    settings_export_name: String,
-    middlewares_export_name: String,
-    tail_middlewares_export_name: String,
-}
-
-impl Convention {
-    fn default() -> Self {
-        Self {
-            server_template: SERVER_TEMPLATE.to_string(),
+    #[arg(long, global = true, default_value = "nexp-compiled")]
+    dist_dir: String,
 
-            app_dir_name: "app".to_string(),
-            route_file_basename: "route".to_string(),
-            support_ext: vec![".ts".to_string(), ".js".to_string()],
-            middlewares_file_basename: "middlewares".to_string(),
-            tail_middlewares_file_basename: "tail-middlewares".to_string(),
-            settings_file_basename: "settings".to_string(),
-            custom_server_basename: "custom-server".to_string(),
+    #[arg(long, default_value = "server.ts")]
+    filename: String,
 
-            settings_export_name: "settings".to_string(),
-            middlewares_export_name: "middlewares".to_string(),
-            tail_middlewares_export_name: "middlewares".to_string(),
-        }
-    }
+    /// Write a JSON manifest of every compiled endpoint to this path
+    #[arg(long)]
+    manifest: Option<String>,
 
-    fn get_filenames(&self, basename: &str) -> Vec<String> {
-        self.support_ext
-            .iter()
-            .map(|ext| format!("{}{}", basename, ext))
-            .collect()
-    }
+    /// Write an OpenAPI 3.1 document describing every endpoint to this path
+    #[arg(long)]
+    openapi: Option<String>,
 
-    fn get_middlewares_filenames(&self) -> Vec<String> {
-        self.get_filenames(&self.middlewares_file_basename)
-    }
+    /// Do not read or write the route exports cache in `<dist-dir>/.nexp-cache`
+    #[arg(long, global = true)]
+    no_cache: bool,
 
-    fn get_tail_middlewares_filenames(&self) -> Vec<String> {
-        self.get_filenames(&self.tail_middlewares_file_basename)
-    }
+    /// Keep running and recompile when routes, middlewares, settings or the custom server change
+    #[cfg(not(target_family = "wasm"))]
+    #[arg(long)]
+    watch: bool,
 
-    fn get_settings_filenames(&self) -> Vec<String> {
-        self.get_filenames(&self.settings_file_basename)
-    }
+    /// Fail on route exports that look like misnamed HTTP method handlers, e.g. `get`
+    #[arg(long, global = true)]
+    strict: bool,
 
-    fn get_route_filenames(&self) -> Vec<String> {
-        self.get_filenames(&self.route_file_basename)
-    }
+    /// How handlers are registered on their router [default: dispatch]
+    #[arg(long, value_enum)]
+    route_style: Option<RouteStyle>,
 
-    fn get_custom_server_filenames(&self) -> Vec<String> {
-        self.get_filenames(&self.custom_server_basename)
-    }
-}
+    /// How the generated server imports the source files [default: bundler]
+    #[arg(long, value_enum)]
+    import_style: Option<ImportStyle>,
 
-#[derive(Debug, Clone)]
-struct Config {
-    method_not_allowed_res: String,
-}
+    /// Project config file overriding the conventions, `nexp.config.json` next to the source
+    /// directory if it exists
+    #[arg(long, global = true)]
+    config: Option<String>,
 
-impl Config {
-    fn default() -> Self {
-        Self {
-            method_not_allowed_res: "res.status(405).send(`Method ${req.method} Not Allowed`);"
-                .to_string(),
-        }
-    }
+    /// How errors and warnings are printed
+    #[arg(long, global = true, value_enum, default_value_t)]
+    message_format: MessageFormat,
 }
 
-#[derive(Debug)]
-struct EndpointHandler {
-    export_name: String,
-    is_async: bool,
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Print the endpoints the server exposes, without generating it
+    Routes {
+        #[arg(long, value_enum, default_value_t)]
+        format: RoutesFormat,
+    },
 }
 
-struct ExportVisitor {
-    exports: Vec<EndpointHandler>,
-}
+fn main() -> ExitCode {
+    let env = env_logger::Env::new().filter_or("NEXP_LOG", "info");
+    env_logger::init_from_env(env);
 
-impl ExportVisitor {
-    fn new() -> Self {
-        Self {
-            exports: Vec::new(),
+    let args = Args::parse();
+    let message_format = args.message_format;
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            report(&e, message_format);
+            ExitCode::FAILURE
         }
     }
 }
 
-impl Visit for ExportVisitor {
-    fn visit_export_decl(&mut self, n: &ExportDecl) {
-        match &n.decl {
-            Decl::Fn(fn_decl) => {
-                let name = fn_decl.ident.sym.to_string();
-                let is_async = fn_decl.function.is_async;
-                self.exports.push(EndpointHandler {
-                    export_name: name,
-                    is_async,
-                });
-            }
-            Decl::Var(var_decl) => {
-                for decl in &var_decl.decls {
-                    if let Pat::Ident(ident) = &decl.name {
-                        let name = ident.id.sym.to_string();
-                        let is_async = if let Some(init) = &decl.init {
-                            match init.as_ref() {
-                                Expr::Arrow(arrow) => arrow.is_async,
-                                _ => false,
-                            }
-                        } else {
-                            false
-                        };
-                        self.exports.push(EndpointHandler {
-                            export_name: name,
-                            is_async,
-                        });
-                    }
-                }
-            }
-            _ => {}
+/// Print diagnostics on stderr. Human warnings are silenced along with the warn log level,
+/// e.g. by `NEXP_LOG=error`, JSON ones never are.
+fn print_diagnostics(diagnostics: &[Diagnostic], message_format: MessageFormat) {
+    for diagnostic in diagnostics {
+        if message_format == MessageFormat::Human
+            && diagnostic.severity == Severity::Warning
+            && !log::log_enabled!(log::Level::Warn)
+        {
+            continue;
         }
+        eprintln!("{}", diagnostic.format(&DiskFs, message_format));
     }
-
-    fn visit_export_default_expr(&mut self, n: &ExportDefaultExpr) {
-        // Handle default exports if needed
-        let is_async = match n.expr.as_ref() {
-            Expr::Arrow(arrow) => arrow.is_async,
-            Expr::Fn(fn_expr) => fn_expr.function.is_async,
-            _ => false,
-        };
-        self.exports.push(EndpointHandler {
-            export_name: "default".to_string(),
-            is_async,
-        });
-    }
-}
-
-fn route_name_to_identifier(name: &str) -> Result<String> {
-    let mut name = name.trim().replace('-', "_");
-    if name.starts_with('(') && name.ends_with(')') {
-        anyhow::bail!("Virtual group should not be used as a route name");
-    }
-    // Handle dynamic route parameters [param] -> param
-    if name.starts_with('[') && name.ends_with(']') {
-        name = name[1..name.len() - 1].to_string();
-    }
-    Ok(name)
-}
-
-fn unique_route_handler_alias(app_route: &AppRoute) -> String {
-    app_route
-        .relative_path
-        .replace('/', "_")
-        .replace('.', "_")
-        .replace('-', "_")
-        .replace('(', "")
-        .replace(')', "")
-        .replace('[', "")
-        .replace(']', "")
-}
-
-fn get_route_display_name(name: &str) -> String {
-    // Convert [param] to :param for display in comments
-    if name.starts_with('[') && name.ends_with(']') {
-        let param_name = &name[1..name.len() - 1];
-        return format!(":{}", param_name);
-    }
-    name.to_string()
 }
 
-fn get_route_display_path(relative_path: &str) -> String {
-    // Convert path segments [param] to :param for display in comments
-    relative_path
-        .split('/')
-        .map(|segment| {
-            if segment.starts_with('[') && segment.ends_with(']') {
-                let param_name = &segment[1..segment.len() - 1];
-                format!(":{}", param_name)
-            } else {
-                segment.to_string()
-            }
-        })
-        .collect::<Vec<_>>()
-        .join("/")
-}
-
-fn find_app_route_recursive_mut<'a>(
-    app_routes: &'a mut [AppRoute],
-    relative_path: &str,
-) -> Option<&'a mut AppRoute> {
-    for route in app_routes {
-        if route.relative_path == relative_path {
-            return Some(route);
-        }
-        if let Some(child_route) = find_app_route_recursive_mut(&mut route.children, relative_path)
-        {
-            return Some(child_route);
-        }
+/// Print every diagnostic of a failed compilation.
+fn report(error: &anyhow::Error, message_format: MessageFormat) {
+    let diagnostics = Diagnostic::from_error(error);
+    print_diagnostics(&diagnostics, message_format);
+    let error_count = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .count();
+    if error_count > 1 && message_format == MessageFormat::Human {
+        eprintln!("error: compilation failed with {} errors", error_count);
     }
-    None
 }
 
-fn get_app_struct(src_dir: &str, dist_dir: &str, convention: &Convention) -> Result<AppStruct> {
-    log::debug!(
-        "Starting to analyze app structure from src_dir: {}",
-        src_dir
-    );
-
-    let src_path = PathBuf::from(src_dir).canonicalize().map_err(|e| {
-        anyhow::anyhow!("Failed to canonicalize source directory {}: {}", src_dir, e)
-    })?;
-    let dist_path = PathBuf::from(dist_dir).canonicalize().map_err(|e| {
-        anyhow::anyhow!("Failed to canonicalize dist directory {}: {}", dist_dir, e)
-    })?;
-
-    log::debug!(
-        "Canonicalized paths - src: {}, dist: {}",
-        src_path.display(),
-        dist_path.display()
-    );
-    let mut app_struct = AppStruct {
-        src_dir: src_dir.to_string(),
-        dist_to_src_relpath: pathdiff::diff_paths(&src_path, &dist_path)
-            .context("Failed to compute dist to src relative path")?
-            .to_string_lossy()
-            .to_string(),
-        app: AppRoute {
-            name: "app".to_string(),
-            relative_path: "app".to_string(),
-            route: None,
-            middlewares: None,
-            children: Vec::new(),
-            sub_router: None,
-        },
-        top_level_middlewares: None,
-        tail_middlewares: None,
-        settings: None,
+fn run(args: Args) -> Result<()> {
+    let src_dir = args.src_dir;
+    let dist_dir = args.dist_dir;
+    let filename = args.filename;
+    let start_time = std::time::Instant::now();
+    let mut convention = Convention::default();
+    let mut config = Config::default();
+    ProjectConfig::load(&DiskFs, args.config.as_deref(), &src_dir)?
+        .apply(&mut convention, &mut config);
+    // Flags win over the project config
+    if let Some(route_style) = args.route_style {
+        config.route_style = route_style;
+    }
+    if let Some(import_style) = args.import_style {
+        config.import_style = import_style;
+    }
+    if args.strict {
+        config.strict = true;
+    }
+    convention.apply_import_style(config.import_style);
+    let mut cache = if args.no_cache {
+        ExportsCache::disabled()
+    } else {
+        ExportsCache::load(&DiskFs, &dist_dir, &convention)
     };
 
-    log::info!("Scanning directory structure in: {}", src_path.display());
-    let mut file_count = 0;
-    let mut dir_count = 0;
-
-    for entry in WalkDir::new(&src_path) {
-        let entry = entry?;
-        let path = entry.path();
-        let relative_path =
-            pathdiff::diff_paths(path, &src_path).context("Failed to compute relative path")?;
-        let relative_path_str = relative_path.to_string_lossy().to_string();
-
-        let is_inside_app_dir = relative_path_str.starts_with(&convention.app_dir_name);
-        let parent_path = path.parent().unwrap_or(&src_path);
-        let parent_relative_path = pathdiff::diff_paths(parent_path, &src_path)
-            .unwrap_or_default()
-            .to_string_lossy()
-            .to_string();
-        let is_direct_children_of_app_dir = parent_path == src_path.join(&convention.app_dir_name);
-
-        if entry.file_type().is_dir() {
-            dir_count += 1;
-            log::debug!("Found directory: {}", relative_path_str);
-
-            if is_direct_children_of_app_dir {
-                app_struct.app.children.push(AppRoute {
-                    name: entry.file_name().to_string_lossy().to_string(),
-                    relative_path: relative_path_str,
-                    route: None,
-                    middlewares: None,
-                    children: Vec::new(),
-                    sub_router: None,
-                });
-            } else if is_inside_app_dir {
-                if let Some(app_route) = find_app_route_recursive_mut(
-                    &mut app_struct.app.children,
-                    &parent_relative_path,
-                ) {
-                    app_route.children.push(AppRoute {
-                        name: entry.file_name().to_string_lossy().to_string(),
-                        relative_path: relative_path_str,
-                        route: None,
-                        middlewares: None,
-                        children: Vec::new(),
-                        sub_router: None,
-                    });
-                }
-            }
-        } else {
-            file_count += 1;
-            log::debug!("Processing file: {}", relative_path_str);
-
-            // Handle files
-            let filename = entry.file_name().to_string_lossy();
-
-            // Handle top-level files
-            let middleware_names = convention.get_middlewares_filenames();
-            if middleware_names.contains(&relative_path_str) {
-                log::info!("Found top-level middleware file: {}", relative_path_str);
-                app_struct.top_level_middlewares = Some(relative_path_str.clone());
-            }
-
-            let tail_middlewares_names = convention.get_tail_middlewares_filenames();
-            if tail_middlewares_names.contains(&relative_path_str) {
-                log::info!("Found tail middleware file: {}", relative_path_str);
-                app_struct.tail_middlewares = Some(relative_path_str.clone());
-            }
-
-            let settings_names = convention.get_settings_filenames();
-            if settings_names.contains(&relative_path_str) {
-                log::info!("Found settings file: {}", relative_path_str);
-                app_struct.settings = Some(relative_path_str.clone());
-            }
-
-            // Handle route and middleware files
-            let route_names = convention.get_route_filenames();
-            let middleware_names = convention.get_middlewares_filenames();
-
-            if is_direct_children_of_app_dir {
-                if route_names.contains(&filename.to_string()) {
-                    log::info!("Found app-level route file: app/{}", filename);
-                    app_struct.app.route = Some(filename.to_string());
-                }
-                if middleware_names.contains(&filename.to_string()) {
-                    log::info!("Found app-level middleware file: app/{}", filename);
-                    app_struct.app.middlewares = Some(filename.to_string());
-                }
-            } else if is_inside_app_dir {
-                if let Some(app_route) = find_app_route_recursive_mut(
-                    &mut app_struct.app.children,
-                    &parent_relative_path,
-                ) {
-                    let is_virtual_group_dir =
-                        app_route.name.starts_with('(') && app_route.name.ends_with(')');
-                    if !is_virtual_group_dir {
-                        if route_names.contains(&filename.to_string()) {
-                            log::info!(
-                                "Found route file: {}/{}",
-                                app_route.relative_path,
-                                filename
-                            );
-                            app_route.route = Some(filename.to_string());
-                        }
-                        if middleware_names.contains(&filename.to_string()) {
-                            log::info!(
-                                "Found middleware file: {}/{}",
-                                app_route.relative_path,
-                                filename
-                            );
-                            app_route.middlewares = Some(filename.to_string());
-                        }
-                    }
-                }
-            }
+    if let Some(Command::Routes { format }) = args.command {
+        // Listing routes writes nothing, the cache is only kept in an existing dist directory
+        if !DiskFs.exists(Path::new(&dist_dir)) {
+            cache = ExportsCache::disabled();
         }
+        let mut app_struct = scan(&DiskFs, &src_dir, &dist_dir, &convention)?;
+        analyze(&DiskFs, &mut app_struct, &convention, &config, &mut cache)?;
+        print_diagnostics(&app_struct.warnings, args.message_format);
+        print!("{}", routes::render_routes(&app_struct, format)?);
+        return Ok(());
     }
 
     log::info!(
-        "Directory scan completed: {} directories, {} files processed",
-        dir_count,
-        file_count
+        "Compiling next-express from {}, output to {}/{}",
+        src_dir,
+        dist_dir,
+        filename
     );
-    log::debug!("App structure analysis completed");
-
-    Ok(app_struct)
-}
-
-fn rel_path_to_endpoint(rel_path: &str) -> Result<String> {
-    if !rel_path.starts_with("app/") || !rel_path.ends_with("route.ts") {
-        anyhow::bail!("Invalid route path: {}", rel_path);
-    }
-
-    let rel_path_without_app = &rel_path["app/".len()..];
-    let segments: Vec<&str> = rel_path_without_app.split('/').collect();
-    let mut endpoint = String::new();
-
-    for segment in segments {
-        if segment.starts_with('(') && segment.ends_with(')') {
-            continue;
-        }
-        if segment == "route.ts" {
-            endpoint.push('/');
-        } else if segment.starts_with('[') && segment.ends_with(']') {
-            // Convert [param] to :param for Express dynamic routes
-            let param_name = &segment[1..segment.len() - 1];
-            endpoint.push_str("/:");
-            endpoint.push_str(param_name);
-        } else {
-            endpoint.push('/');
-            endpoint.push_str(segment);
+    let write_artifacts = |app_struct: &AppStruct| -> Result<()> {
+        print_diagnostics(&app_struct.warnings, args.message_format);
+        if let Some(manifest_path) = &args.manifest {
+            manifest::write_manifest(&DiskFs, app_struct, manifest_path)?;
         }
-    }
-
-    Ok(endpoint)
-}
-
-fn get_endpoint_handlers(abs_path: &Path) -> Result<Vec<EndpointHandler>> {
-    log::debug!("Parsing endpoint handlers from: {}", abs_path.display());
-
-    let source_map: Lrc<SourceMap> = Default::default();
-    let source_file = source_map.load_file(abs_path)?;
-
-    let lexer = Lexer::new(
-        Syntax::Typescript(TsSyntax {
-            tsx: false,
-            decorators: true,
-            dts: false,
-            no_early_errors: true,
-            disallow_ambiguous_jsx_like: true,
-        }),
-        EsVersion::Es2022,
-        StringInput::from(&*source_file),
-        None,
-    );
-
-    let mut parser = Parser::new_from(lexer);
-    let module = parser
-        .parse_module()
-        .map_err(|e| anyhow::anyhow!("Parse error: {:?}", e))?;
-
-    let mut visitor = ExportVisitor::new();
-    module.visit_with(&mut visitor);
-
-    log::debug!(
-        "Found {} endpoint handlers in {}",
-        visitor.exports.len(),
-        abs_path.display()
-    );
-    for handler in &visitor.exports {
-        log::debug!("  - {} (async: {})", handler.export_name, handler.is_async);
-    }
-
-    Ok(visitor.exports)
-}
-
-fn compile_route(
-    imports: &mut String,
-    routes: &mut String,
-    app_route: &mut AppRoute,
-    src_dir: &str,
-    dist_to_src_relpath: &str,
-    nearest_sub_router: Option<&SubRouter>,
-    convention: &Convention,
-    config: &Config,
-) -> Result<()> {
-    log::debug!(
-        "Compiling route: {} at {}",
-        app_route.name,
-        app_route.relative_path
-    );
-
-    let display_name = get_route_display_name(&app_route.name);
-    let display_path = get_route_display_path(&app_route.relative_path);
-    routes.push_str(&format!(
-        "// ===== routes [{} | {}] =====\n",
-        display_name, display_path
-    ));
-
-    let mut current_nearest_sub_router = nearest_sub_router;
-
-    if let Some(_middlewares) = &app_route.middlewares {
-        log::debug!("Setting up middleware router for: {}", app_route.name);
-        // Calculate the full path from app root by converting relative_path to endpoint
-        // e.g., "app/manage/admin" -> "/manage/admin"
-        let full_router_path = format!(
-            "/{}",
-            app_route
-                .relative_path
-                .strip_prefix("app/")
-                .unwrap_or(&app_route.relative_path)
-        );
-        // If there's a parent sub-router, we need to make this path relative to it
-        let group_route_path = if let Some(sub_router) = nearest_sub_router {
-            full_router_path.replace(&sub_router.path, "")
-        } else {
-            full_router_path.clone()
-        };
-        let route_identifier = route_name_to_identifier(&app_route.name)?;
-        let route_middlewares_alias = format!("{}Middlewares", route_identifier);
-
-        imports.push_str(&format!(
-            "import {{ {} as {} }} from \"{}/{}/{}\";\n",
-            convention.middlewares_export_name,
-            route_middlewares_alias,
-            dist_to_src_relpath,
-            app_route.relative_path,
-            app_route
-                .middlewares
-                .as_ref()
-                .unwrap()
-                .replace(".ts", "")
-                .replace(".js", "")
-        ));
-
-        let group_router_identifier = format!("{}Router", route_identifier);
-        // Store the full path from app root for child routes to use
-        app_route.sub_router = Some(SubRouter {
-            identifier: group_router_identifier.clone(),
-            path: full_router_path,
-        });
-        // Use the parent router if available, otherwise use app
-        let parent_router = nearest_sub_router
-            .map(|s| s.identifier.as_str())
-            .unwrap_or("app");
-        current_nearest_sub_router = app_route.sub_router.as_ref();
-
-        routes.push_str(&format!(
-            "const {} = express.Router();\n",
-            group_router_identifier
-        ));
-        routes.push_str(&format!(
-            "{}.use(\"{}\", {});\n",
-            parent_router, group_route_path, group_router_identifier
-        ));
-        routes.push_str(&format!(
-            "{}.use(...{});\n",
-            group_router_identifier, route_middlewares_alias
-        ));
-    }
-
-    if let Some(route) = &app_route.route {
-        log::debug!("Processing route handlers for: {}", app_route.name);
-
-        let mut endpoint_uri =
-            rel_path_to_endpoint(&format!("{}/{}", app_route.relative_path, route))?;
-        if let Some(sub_router) = current_nearest_sub_router {
-            endpoint_uri = endpoint_uri.replace(&sub_router.path, "");
+        if let Some(openapi_path) = &args.openapi {
+            openapi::write_openapi(&DiskFs, app_struct, openapi_path)?;
         }
+        Ok(())
+    };
 
-        log::debug!(
-            "Mapped endpoint URI: {} for route: {}",
-            endpoint_uri,
-            app_route.name
-        );
-
-        let route_abs_path = PathBuf::from(src_dir)
-            .join(&app_route.relative_path)
-            .join(route);
-        let handlers = match get_endpoint_handlers(&route_abs_path) {
-            Ok(handlers) => {
-                if handlers.is_empty() {
-                    return Err(anyhow::anyhow!(
-                        "No valid endpoint handlers found in {}",
-                        route_abs_path.display()
-                    ));
+    #[cfg(not(target_family = "wasm"))]
+    if args.watch {
+        return watch::watch(
+            &src_dir,
+            &dist_dir,
+            &filename,
+            &convention,
+            &config,
+            &mut cache,
+            args.openapi.is_some(),
+            |result| {
+                if let Err(e) = result.and_then(write_artifacts) {
+                    report(&e, args.message_format);
                 }
-                handlers
-            }
-            Err(e) => {
-                return Err(anyhow::anyhow!(
-                    "Failed to get endpoint handlers for {}: {}",
-                    route_abs_path.display(),
-                    e
-                ));
-            }
-        };
-
-        let mut endpoint_handler_inner = String::new();
-        for handler in handlers {
-            let handler_alias = format!(
-                "{}_{}",
-                unique_route_handler_alias(app_route),
-                handler.export_name
-            );
-            imports.push_str(&format!(
-                "import {{ {} as {} }} from \"{}/{}/{}\";\n",
-                handler.export_name,
-                handler_alias,
-                dist_to_src_relpath,
-                app_route.relative_path,
-                app_route
-                    .route
-                    .as_ref()
-                    .unwrap()
-                    .replace(".ts", "")
-                    .replace(".js", "")
-            ));
-            endpoint_handler_inner.push_str(&format!(
-                "if (req.method === \"{}\") {{ {}{}(req, res); return; }}\n",
-                handler.export_name.to_uppercase(),
-                if handler.is_async { "await " } else { "" },
-                handler_alias
-            ));
-        }
-
-        let router = current_nearest_sub_router
-            .map(|s| s.identifier.as_str())
-            .unwrap_or("app");
-        let endpoint_handler = format!(
-            "{}.all(\"{}\", async (req, res) => {{ {} {} }});\n",
-            router, endpoint_uri, endpoint_handler_inner, config.method_not_allowed_res,
-        );
-        routes.push_str(&endpoint_handler);
-    }
-
-    routes.push('\n');
-    Ok(())
-}
-
-#[derive(Debug)]
-struct CompiledAppStruct {
-    imports: String,
-    settings: String,
-    top_level_middlewares: String,
-    routes: String,
-    tail_middlewares: String,
-}
-
-fn compile_app_struct(
-    app_struct: &mut AppStruct,
-    convention: &Convention,
-    config: &Config,
-) -> Result<CompiledAppStruct> {
-    log::info!("Starting app structure compilation");
-
-    let mut imports = String::new();
-    let mut settings = String::new();
-    let mut top_level_middlewares = String::new();
-    let mut routes = String::new();
-    let mut tail_middlewares = String::new();
-
-    if let Some(settings_file) = &app_struct.settings {
-        log::debug!("Adding settings import from: {}", settings_file);
-        imports.push_str(&format!(
-            "import {{ {} as appSettings }} from \"{}/{}\";\n",
-            convention.settings_export_name,
-            app_struct.dist_to_src_relpath,
-            settings_file.replace(".ts", "").replace(".js", "")
-        ));
-        settings.push_str("for (const setting of appSettings) {\n      app.set(setting.name, setting.value);\n    }\n");
-    }
-
-    if let Some(middlewares_file) = &app_struct.top_level_middlewares {
-        log::debug!(
-            "Adding top-level middlewares import from: {}",
-            middlewares_file
-        );
-        imports.push_str(&format!(
-            "import {{ {} as topLevelMiddlewares }} from \"{}/{}\";\n",
-            convention.middlewares_export_name,
-            app_struct.dist_to_src_relpath,
-            middlewares_file.replace(".ts", "").replace(".js", "")
-        ));
-        top_level_middlewares.push_str("app.use(...topLevelMiddlewares);\n");
-    }
-
-    if let Some(tail_middlewares_file) = &app_struct.tail_middlewares {
-        log::debug!(
-            "Adding tail-middlewares handler import from: {}",
-            tail_middlewares_file
-        );
-        imports.push_str(&format!(
-            "import {{ {} as tailMiddlewares }} from \"{}/{}\";\n",
-            convention.tail_middlewares_export_name,
-            app_struct.dist_to_src_relpath,
-            tail_middlewares_file.replace(".ts", "").replace(".js", "")
-        ));
-        tail_middlewares.push_str("app.use(...tailMiddlewares);\n");
-    }
-
-    fn traverse_route(
-        app_route: &mut AppRoute,
-        imports: &mut String,
-        routes: &mut String,
-        src_dir: &str,
-        dist_to_src_relpath: &str,
-        nearest_sub_router: Option<&SubRouter>,
-        convention: &Convention,
-        config: &Config,
-    ) -> Result<()> {
-        log::debug!(
-            "Traversing route: {} (children: {})",
-            app_route.name,
-            app_route.children.len()
+            },
         );
-
-        if app_route.route.is_some() || app_route.middlewares.is_some() {
-            compile_route(
-                imports,
-                routes,
-                app_route,
-                src_dir,
-                dist_to_src_relpath,
-                nearest_sub_router,
-                convention,
-                config,
-            )?;
-        }
-
-        let current_sub_router = app_route.sub_router.as_ref().or(nearest_sub_router);
-
-        for child in &mut app_route.children {
-            traverse_route(
-                child,
-                imports,
-                routes,
-                src_dir,
-                dist_to_src_relpath,
-                current_sub_router,
-                convention,
-                config,
-            )?;
-        }
-
-        Ok(())
     }
 
-    log::info!("Traversing application routes");
-    traverse_route(
-        &mut app_struct.app,
-        &mut imports,
-        &mut routes,
-        &app_struct.src_dir,
-        &app_struct.dist_to_src_relpath,
-        None,
-        convention,
-        config,
+    let app_struct = compile(
+        &DiskFs,
+        &src_dir,
+        &dist_dir,
+        &filename,
+        &convention,
+        &config,
+        &mut cache,
     )?;
-
-    log::info!("App structure compilation completed");
-
-    Ok(CompiledAppStruct {
-        imports,
-        settings,
-        top_level_middlewares,
-        routes,
-        tail_middlewares,
-    })
-}
-
-// for test case, sort app route to match ts-impl
-fn sort_app_route(app_struct: &mut AppRoute) {
-    app_struct
-        .children
-        .sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
-    if app_struct.children.len() > 1 {
-        for child in &mut app_struct.children {
-            sort_app_route(child);
-        }
-    }
-}
-
-fn compile(
-    src_dir: &str,
-    dist_dir: &str,
-    filename: &str,
-    convention: &mut Convention,
-    config: &Config,
-) -> Result<()> {
-    log::info!("Starting compilation process");
-    log::debug!(
-        "Parameters - src_dir: {}, dist_dir: {}, filename: {}",
-        src_dir,
-        dist_dir,
-        filename
-    );
-
-    let custom_server_paths: Vec<PathBuf> = convention
-        .get_custom_server_filenames()
-        .iter()
-        .map(|f| PathBuf::from(src_dir).join(f))
-        .collect();
-    for custom_server_path in &custom_server_paths {
-        if custom_server_path.exists() {
-            log::info!(
-                "Found custom server template at: {}",
-                custom_server_path.display()
-            );
-            println!(
-                "[{}] Found custom server template at {}",
-                env!("CARGO_PKG_NAME"),
-                custom_server_path.display()
-            );
-            convention.server_template = fs::read_to_string(custom_server_path)
-                .context("Failed to read custom server template")?;
-            break;
-        }
-    }
-
-    log::debug!("Ensuring output directory exists");
-    let output_path = Path::new(dist_dir).join(filename);
-    if let Some(parent) = output_path.parent() {
-        fs::create_dir_all(parent)?;
-    }
-
-    log::info!("Building app structure");
-    let mut app_struct = get_app_struct(src_dir, dist_dir, convention)?;
-
-    log::debug!("Sorting app routes for consistent output");
-    sort_app_route(&mut app_struct.app);
-
-    log::info!("Compiling app structure to code");
-    let transformed = compile_app_struct(&mut app_struct, convention, config)?;
-
-    log::debug!("Generating final output from template");
-    let output = convention
-        .server_template
-        .replace("/* __nextExpress_imports__ */", &transformed.imports)
-        .replace("/* __nextExpress_settings__ */", &transformed.settings)
-        .replace(
-            "/* __nextExpress_topLevelMiddlewares__ */",
-            &transformed.top_level_middlewares,
-        )
-        .replace("/* __nextExpress_routes__ */", &transformed.routes)
-        .replace(
-            "/* __nextExpress_tailMiddlewares__ */",
-            &transformed.tail_middlewares,
-        );
-
-    log::info!("Writing output to: {}", output_path.display());
-    fs::write(output_path, output)?;
-
-    log::info!("Compilation completed successfully");
-    Ok(())
-}
-
-#[derive(ClapParser, Debug)]
-#[command(version, about = "A compiler cli for next-express writen in rust.", long_about = None)]
-struct Args {
-    #[arg(long, default_value = "src")]
-    src_dir: String,
-
-    #[arg(long, default_value = "nexp-compiled")]
-    dist_dir: String,
-
-    #[arg(long, default_value = "server.ts")]
-    filename: String,
-}
-
-fn main() -> Result<()> {
-    let env = env_logger::Env::new().filter_or("NEXP_LOG", "info");
-    env_logger::init_from_env(env);
-
-    let args = Args::parse();
-
-    let src_dir = args.src_dir;
-    let dist_dir = args.dist_dir;
-    let filename = args.filename;
-
-    log::info!(
-        "Compiling next-express from {}, output to {}/{}",
-        src_dir,
-        dist_dir,
-        filename
-    );
-
-    let start_time = std::time::Instant::now();
-    let mut convention = Convention::default();
-    let config = Config::default();
-    compile(&src_dir, &dist_dir, &filename, &mut convention, &config)?;
+    write_artifacts(&app_struct)?;
 
     log::info!(
         "Compiling completed successfully in {}ms!",
@@ -0,0 +1,135 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use crate::methods::{FALLBACK_EXPORT, allowed_methods};
+use crate::routes::route_middlewares;
+use crate::vfs::FileSystem;
+use crate::{AppRoute, AppStruct, rel_path_to_endpoint};
+
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteManifest {
+    pub version: u32,
+    pub settings: Option<String>,
+    pub tail_middlewares: Option<String>,
+    pub endpoints: Vec<ManifestEndpoint>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestEndpoint {
+    /// Full URL pattern from the app root, in Express path syntax
+    pub url: String,
+    /// Methods the endpoint responds to, including the automatic `HEAD` and `OPTIONS`
+    pub methods: Vec<String>,
+    pub handlers: Vec<ManifestHandler>,
+    /// Route file, relative to the source directory
+    pub source: String,
+    /// Middleware files, then the middleware exports of the route, outer to inner, e.g.
+    /// `app/users/route.ts#POST_middlewares` which only guards `POST`
+    pub middlewares: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestHandler {
+    /// `*` for the default export, which handles every other method
+    pub method: String,
+    pub export_name: String,
+    pub is_async: bool,
+}
+
+/// Collect every endpoint of a compiled app structure.
+///
+/// Must be called after `analyze`, which fills in the handlers of each route.
+pub fn build_manifest(app_struct: &AppStruct) -> Result<RouteManifest> {
+    fn traverse(
+        app_route: &AppRoute,
+        middlewares: &mut Vec<String>,
+        endpoints: &mut Vec<ManifestEndpoint>,
+    ) -> Result<()> {
+        let has_middlewares = if let Some(middlewares_file) = &app_route.middlewares {
+            middlewares.push(format!("{}/{}", app_route.relative_path, middlewares_file));
+            true
+        } else {
+            false
+        };
+
+        if let Some(route) = app_route.enabled_route() {
+            let source = format!("{}/{}", app_route.relative_path, route);
+            let handlers = app_route
+                .exports
+                .handlers
+                .iter()
+                .map(|handler| ManifestHandler {
+                    method: if handler.export_name == FALLBACK_EXPORT {
+                        "*".to_string()
+                    } else {
+                        handler.export_name.to_uppercase()
+                    },
+                    export_name: handler.export_name.clone(),
+                    is_async: handler.is_async,
+                })
+                .collect::<Vec<_>>();
+            let mut chain = middlewares.clone();
+            chain.extend(route_middlewares(app_route, &source));
+            endpoints.push(ManifestEndpoint {
+                url: rel_path_to_endpoint(&source)?,
+                methods: allowed_methods(&app_route.exports),
+                handlers,
+                source,
+                middlewares: chain,
+            });
+        }
+
+        for child in &app_route.children {
+            traverse(child, middlewares, endpoints)?;
+        }
+
+        if has_middlewares {
+            middlewares.pop();
+        }
+        Ok(())
+    }
+
+    let mut middlewares = Vec::new();
+    if let Some(top_level_middlewares) = &app_struct.top_level_middlewares {
+        middlewares.push(top_level_middlewares.clone());
+    }
+    let mut endpoints = Vec::new();
+    traverse(&app_struct.app, &mut middlewares, &mut endpoints)?;
+
+    Ok(RouteManifest {
+        version: MANIFEST_VERSION,
+        settings: app_struct.settings.clone(),
+        tail_middlewares: app_struct.tail_middlewares.clone(),
+        endpoints,
+    })
+}
+
+pub fn write_manifest(
+    fs: &dyn FileSystem,
+    app_struct: &AppStruct,
+    manifest_path: &str,
+) -> Result<()> {
+    let manifest = build_manifest(app_struct)?;
+    let output_path = Path::new(manifest_path);
+    if let Some(parent) = output_path.parent() {
+        fs.create_dir_all(parent)?;
+    }
+
+    log::info!(
+        "Writing route manifest with {} endpoints to: {}",
+        manifest.endpoints.len(),
+        output_path.display()
+    );
+    let mut json =
+        serde_json::to_string_pretty(&manifest).context("Failed to serialize route manifest")?;
+    json.push('\n');
+    fs.write(output_path, &json)
+        .context("Failed to write route manifest")?;
+    Ok(())
+}
@@ -0,0 +1,151 @@
+use crate::exports::RouteExports;
+
+/// Methods Express can route (Node's `http.METHODS`), except `M-SEARCH` which is not a valid
+/// export name.
+pub const HTTP_METHODS: [&str; 34] = [
+    "ACL",
+    "BIND",
+    "CHECKOUT",
+    "CONNECT",
+    "COPY",
+    "DELETE",
+    "GET",
+    "HEAD",
+    "LINK",
+    "LOCK",
+    "MERGE",
+    "MKACTIVITY",
+    "MKCALENDAR",
+    "MKCOL",
+    "MOVE",
+    "NOTIFY",
+    "OPTIONS",
+    "PATCH",
+    "POST",
+    "PROPFIND",
+    "PROPPATCH",
+    "PURGE",
+    "PUT",
+    "QUERY",
+    "REBIND",
+    "REPORT",
+    "SEARCH",
+    "SOURCE",
+    "SUBSCRIBE",
+    "TRACE",
+    "UNBIND",
+    "UNLINK",
+    "UNLOCK",
+    "UNSUBSCRIBE",
+];
+
+/// Methods a misnamed export is compared with. The other ones are words like `search` or
+/// `link` that helpers are commonly named after.
+const COMMON_METHODS: [&str; 7] = ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"];
+
+/// Name of the export handling every method a route does not export a handler for.
+pub const FALLBACK_EXPORT: &str = "default";
+
+/// What an export of a route file is, judging by its name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+    /// An HTTP method handler, e.g. `GET`
+    Method,
+    /// The default export, used for methods without their own handler
+    Fallback,
+    /// A method name with the wrong case, e.g. `get`
+    Miscased(&'static str),
+    /// An uppercase name close to a method name, e.g. `DELTE`
+    Misspelled(&'static str),
+    /// Anything else, e.g. a helper or a schema
+    Other,
+}
+
+pub fn is_http_method(name: &str) -> bool {
+    HTTP_METHODS.contains(&name)
+}
+
+/// Whether an export is routed, either as a method handler or as the fallback.
+pub fn is_handler_export(name: &str) -> bool {
+    name == FALLBACK_EXPORT || is_http_method(name)
+}
+
+pub fn classify_export(name: &str) -> ExportKind {
+    if is_http_method(name) {
+        return ExportKind::Method;
+    }
+    if name == FALLBACK_EXPORT {
+        return ExportKind::Fallback;
+    }
+    let upper_name = name.to_ascii_uppercase();
+    if let Some(method) = COMMON_METHODS.iter().find(|m| **m == upper_name) {
+        return ExportKind::Miscased(method);
+    }
+    // Only all-caps names look like an attempt at a method, `getUser` or `set` are helpers
+    if name.chars().all(|c| c.is_ascii_uppercase()) {
+        let closest = COMMON_METHODS
+            .iter()
+            .map(|method| (method, edit_distance(name, method)))
+            .min_by_key(|(_, distance)| *distance);
+        if let Some((method, distance)) = closest
+            && distance <= if method.len() <= 4 { 1 } else { 2 }
+        {
+            return ExportKind::Misspelled(method);
+        }
+    }
+    ExportKind::Other
+}
+
+/// Methods a route responds to, as listed in its `Allow` header: the exported handlers,
+/// plus `HEAD` (when there is a `GET`) and `OPTIONS` which are answered automatically.
+/// A route with a fallback handler lists `*` instead of the automatic methods.
+pub fn allowed_methods(exports: &RouteExports) -> Vec<String> {
+    let mut methods = exports
+        .handlers
+        .iter()
+        .map(|handler| {
+            if handler.export_name == FALLBACK_EXPORT {
+                "*".to_string()
+            } else {
+                handler.export_name.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+    let has_method = |methods: &[String], method: &str| methods.iter().any(|m| m == method);
+    if has_method(&methods, "*") {
+        return methods;
+    }
+    if has_method(&methods, "GET") && !has_method(&methods, "HEAD") {
+        methods.push("HEAD".to_string());
+    }
+    if !has_method(&methods, "OPTIONS") {
+        methods.push("OPTIONS".to_string());
+    }
+    methods
+}
+
+/// Levenshtein distance counting an adjacent transposition as a single edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+            distances[i][j] = distance;
+        }
+    }
+    distances[a.len()][b.len()]
+}
@@ -0,0 +1,228 @@
+use anyhow::{Context, Result};
+use serde_json::{Map, Value, json};
+use std::path::Path;
+
+use crate::identifiers::Identifiers;
+use crate::vfs::FileSystem;
+use crate::{AppRoute, AppStruct, RouteSegment, app_relative_segments, unique_route_handler_alias};
+
+const OPENAPI_VERSION: &str = "3.1.0";
+
+/// Methods that can be described by an OpenAPI path item.
+const OPERATION_METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// Convert a route directory to the OpenAPI path templates it matches and their path
+/// parameters, e.g. "app/todos/[id]" -> "/todos/{id}".
+///
+/// Path parameters are always required, so an optional catch-all is documented as two paths:
+/// the one without it, then the one with it, e.g. "app/docs/[[...slug]]" -> "/docs" and
+/// "/docs/{slug}".
+fn route_to_openapi_paths(relative_path: &str) -> Vec<(String, Vec<Value>)> {
+    let mut paths = Vec::new();
+    let mut path = String::new();
+    let mut parameters = Vec::new();
+    for segment in app_relative_segments(relative_path) {
+        let (name, description) = match RouteSegment::parse(segment) {
+            RouteSegment::Static(name) => {
+                path.push('/');
+                path.push_str(name);
+                continue;
+            }
+            RouteSegment::VirtualGroup(_) => continue,
+            RouteSegment::Dynamic(name) => (name, None),
+            // OpenAPI has no multi-segment parameters, document them as a single one
+            RouteSegment::CatchAll(name) => (name, Some("Matches one or more path segments")),
+            RouteSegment::OptionalCatchAll(name) => {
+                let parent_path = if path.is_empty() { "/" } else { path.as_str() };
+                paths.push((parent_path.to_string(), parameters.clone()));
+                (name, Some("Matches one or more path segments"))
+            }
+        };
+        path.push_str(&format!("/{{{}}}", name));
+        let mut parameter = json!({
+            "name": name,
+            "in": "path",
+            "required": true,
+            "schema": { "type": "string" },
+        });
+        if let Some(description) = description {
+            parameter["description"] = json!(description);
+        }
+        parameters.push(parameter);
+    }
+
+    if path.is_empty() {
+        path.push('/');
+    }
+    paths.push((path, parameters));
+    paths
+}
+
+/// Split a doc comment into an OpenAPI summary (first paragraph) and description (the rest).
+/// Block tags are dropped, except `@deprecated` which is reported separately.
+fn parse_doc(doc: &str) -> (Option<String>, Option<String>, bool) {
+    let mut deprecated = false;
+    let mut paragraphs: Vec<Vec<&str>> = vec![Vec::new()];
+    for line in doc.lines() {
+        if line.starts_with('@') {
+            deprecated |= line.starts_with("@deprecated");
+            continue;
+        }
+        if line.is_empty() {
+            if !paragraphs.last().unwrap().is_empty() {
+                paragraphs.push(Vec::new());
+            }
+        } else {
+            paragraphs.last_mut().unwrap().push(line);
+        }
+    }
+    let mut paragraphs = paragraphs.into_iter().filter(|p| !p.is_empty());
+
+    let summary = paragraphs.next().map(|p| p.join(" "));
+    let description = paragraphs.map(|p| p.join("\n")).collect::<Vec<_>>();
+    let description = if description.is_empty() {
+        None
+    } else {
+        Some(description.join("\n\n"))
+    };
+    (summary, description, deprecated)
+}
+
+/// Deep merge `patch` into `target`, values from `patch` win.
+fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+fn build_path_item(
+    app_route: &AppRoute,
+    parameters: Vec<Value>,
+    operation_ids: &mut Identifiers,
+) -> Value {
+    let mut path_item = Map::new();
+    if !parameters.is_empty() {
+        path_item.insert("parameters".to_string(), Value::Array(parameters));
+    }
+
+    for handler in &app_route.exports.handlers {
+        let method = handler.export_name.to_lowercase();
+        if !OPERATION_METHODS.contains(&method.as_str()) {
+            log::debug!(
+                "Skipping export {} of {}, not an OpenAPI operation",
+                handler.export_name,
+                app_route.relative_path
+            );
+            continue;
+        }
+
+        // Distinct across the document, e.g. for the `user-home` and `user_home` directories
+        // or both paths of an optional catch-all
+        let operation_id = operation_ids.declare(&format!(
+            "{}_{}",
+            unique_route_handler_alias(app_route),
+            handler.export_name
+        ));
+        let mut operation = Map::new();
+        operation.insert("operationId".to_string(), json!(operation_id));
+        if let Some(doc) = &handler.doc {
+            let (summary, description, deprecated) = parse_doc(doc);
+            if let Some(summary) = summary {
+                operation.insert("summary".to_string(), json!(summary));
+            }
+            if let Some(description) = description {
+                operation.insert("description".to_string(), json!(description));
+            }
+            if deprecated {
+                operation.insert("deprecated".to_string(), json!(true));
+            }
+        }
+        path_item.insert(method, Value::Object(operation));
+    }
+
+    // Keys of the `openapi` export named after a method patch that operation,
+    // everything else patches the path item itself. The analysis drops non-object exports.
+    let mut path_item = Value::Object(path_item);
+    if let Some(Value::Object(overrides)) = &app_route.exports.openapi {
+        for (key, value) in overrides.clone() {
+            let lower_key = key.to_lowercase();
+            let key = if OPERATION_METHODS.contains(&lower_key.as_str()) {
+                lower_key
+            } else {
+                key
+            };
+            merge_json(&mut path_item[key], value);
+        }
+    }
+    path_item
+}
+
+/// Read the document title and version from the package.json of the current project.
+fn project_info(fs: &dyn FileSystem) -> Value {
+    let pkg_json = fs
+        .read_to_string(Path::new("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .unwrap_or(Value::Null);
+    json!({
+        "title": pkg_json["name"].as_str().unwrap_or("next-express"),
+        "version": pkg_json["version"].as_str().unwrap_or("0.0.0"),
+    })
+}
+
+/// Build an OpenAPI document with one path item per endpoint.
+///
+/// Must be called after `analyze`, which fills in the exports of each route.
+pub fn build_openapi(fs: &dyn FileSystem, app_struct: &AppStruct) -> Value {
+    fn traverse(
+        app_route: &AppRoute,
+        paths: &mut Map<String, Value>,
+        operation_ids: &mut Identifiers,
+    ) {
+        if app_route.enabled_route().is_some() {
+            for (path, parameters) in route_to_openapi_paths(&app_route.relative_path) {
+                let path_item = build_path_item(app_route, parameters, operation_ids);
+                merge_json(paths.entry(path).or_insert(Value::Null), path_item);
+            }
+        }
+        for child in &app_route.children {
+            traverse(child, paths, operation_ids);
+        }
+    }
+
+    let mut paths = Map::new();
+    traverse(&app_struct.app, &mut paths, &mut Identifiers::new());
+
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": project_info(fs),
+        "paths": paths,
+    })
+}
+
+pub fn write_openapi(
+    fs: &dyn FileSystem,
+    app_struct: &AppStruct,
+    openapi_path: &str,
+) -> Result<()> {
+    let document = build_openapi(fs, app_struct);
+    let output_path = Path::new(openapi_path);
+    if let Some(parent) = output_path.parent() {
+        fs.create_dir_all(parent)?;
+    }
+
+    log::info!("Writing OpenAPI document to: {}", output_path.display());
+    let mut json =
+        serde_json::to_string_pretty(&document).context("Failed to serialize OpenAPI document")?;
+    json.push('\n');
+    fs.write(output_path, &json)
+        .context("Failed to write OpenAPI document")?;
+    Ok(())
+}
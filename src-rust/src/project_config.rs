@@ -0,0 +1,265 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use crate::diagnostics::{Code, Diagnostic, Position};
+use crate::vfs::FileSystem;
+use crate::{Config, Convention, ImportStyle, RouteStyle};
+
+/// Looked up in the project root, the parent of the source directory, when no config file
+/// is given.
+pub const CONFIG_FILE_NAME: &str = "nexp.config.json";
+
+/// Overrides of [`Convention`], every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ConventionOverrides {
+    server_template: Option<String>,
+    app_dir_name: Option<String>,
+    support_ext: Option<Vec<String>>,
+    route_file_basename: Option<String>,
+    middlewares_file_basename: Option<String>,
+    tail_middlewares_file_basename: Option<String>,
+    settings_file_basename: Option<String>,
+    custom_server_basename: Option<String>,
+    settings_export_name: Option<String>,
+    middlewares_export_name: Option<String>,
+    tail_middlewares_export_name: Option<String>,
+    openapi_export_name: Option<String>,
+    config_export_name: Option<String>,
+}
+
+/// Overrides of [`Config`], every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ConfigOverrides {
+    route_style: Option<RouteStyle>,
+    import_style: Option<ImportStyle>,
+    method_not_allowed_res: Option<String>,
+    options_res: Option<String>,
+    timeout_res: Option<String>,
+    strict: Option<bool>,
+}
+
+/// Content of `nexp.config.json`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ProjectConfig {
+    #[serde(default)]
+    convention: ConventionOverrides,
+    #[serde(default)]
+    config: ConfigOverrides,
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+impl ProjectConfig {
+    /// Load `path`, or `nexp.config.json` of the project holding `src_dir` if it exists.
+    ///
+    /// The project root is the parent of the source directory, so that `--src-dir
+    /// packages/api/src` picks up `packages/api/nexp.config.json` from any working directory.
+    /// An explicit `path` is used as given.
+    pub fn load(fs: &dyn FileSystem, path: Option<&str>, src_dir: &str) -> Result<Self> {
+        let default_path = Self::default_path(src_dir);
+        let path = match path {
+            Some(path) => Path::new(path),
+            None if fs.exists(&default_path) => default_path.as_path(),
+            None => return Ok(Self::default()),
+        };
+        let content = fs
+            .read_to_string(path)
+            .with_context(|| format!("Failed to read project config {}", path.display()))?;
+        let deserializer = &mut serde_json::Deserializer::from_str(&content);
+        let project_config: Self = serde_path_to_error::deserialize(deserializer).map_err(|e| {
+            // serde_json reports line 0 for errors without a location
+            let position = (e.inner().line() > 0).then(|| Position {
+                line: e.inner().line(),
+                column: e.inner().column(),
+            });
+            Diagnostic::error(
+                Code::InvalidProjectConfig,
+                format!("Invalid project config at `{}`: {}", e.path(), e.inner()),
+            )
+            .with_file(path)
+            .with_position(position)
+        })?;
+        project_config.validate().map_err(|(key, message)| {
+            Diagnostic::error(
+                Code::InvalidProjectConfig,
+                format!("Invalid project config at `{}`: {}", key, message),
+            )
+            .with_file(path)
+        })?;
+        log::info!("Loaded project config from {}", path.display());
+        Ok(project_config)
+    }
+
+    /// `nexp.config.json` in the parent directory of `src_dir`.
+    pub fn default_path(src_dir: &str) -> PathBuf {
+        Path::new(src_dir)
+            .parent()
+            .unwrap_or(Path::new(""))
+            .join(CONFIG_FILE_NAME)
+    }
+
+    /// Check the values serde accepts but the compiler can not work with,
+    /// returning the offending key and the reason.
+    fn validate(&self) -> Result<(), (String, &'static str)> {
+        let convention = &self.convention;
+        let config = &self.config;
+
+        if let Some(template) = &convention.server_template
+            && !template.contains("/* __nextExpress_routes__ */")
+        {
+            return Err((
+                "convention.serverTemplate".to_string(),
+                "must contain the `/* __nextExpress_routes__ */` placeholder",
+            ));
+        }
+        if let Some(support_ext) = &convention.support_ext {
+            if support_ext.is_empty() {
+                return Err((
+                    "convention.supportExt".to_string(),
+                    "must list at least one extension",
+                ));
+            }
+            if let Some(index) = support_ext
+                .iter()
+                .position(|ext| ext.len() < 2 || !ext.starts_with('.'))
+            {
+                return Err((
+                    format!("convention.supportExt[{}]", index),
+                    "must be an extension starting with a dot, e.g. \".ts\"",
+                ));
+            }
+        }
+
+        let names = [
+            ("appDirName", &convention.app_dir_name),
+            ("routeFileBasename", &convention.route_file_basename),
+            (
+                "middlewaresFileBasename",
+                &convention.middlewares_file_basename,
+            ),
+            (
+                "tailMiddlewaresFileBasename",
+                &convention.tail_middlewares_file_basename,
+            ),
+            ("settingsFileBasename", &convention.settings_file_basename),
+            ("customServerBasename", &convention.custom_server_basename),
+        ];
+        for (key, name) in names {
+            if let Some(name) = name
+                && (name.is_empty() || name.contains(['/', '\\']))
+            {
+                return Err((
+                    format!("convention.{}", key),
+                    "must be a non-empty file or directory name",
+                ));
+            }
+        }
+
+        let export_names = [
+            ("settingsExportName", &convention.settings_export_name),
+            ("middlewaresExportName", &convention.middlewares_export_name),
+            (
+                "tailMiddlewaresExportName",
+                &convention.tail_middlewares_export_name,
+            ),
+            ("openapiExportName", &convention.openapi_export_name),
+            ("configExportName", &convention.config_export_name),
+        ];
+        for (key, name) in export_names {
+            if let Some(name) = name
+                && !is_identifier(name)
+            {
+                return Err((
+                    format!("convention.{}", key),
+                    "must be a valid JavaScript identifier",
+                ));
+            }
+        }
+
+        let responses = [
+            ("methodNotAllowedRes", &config.method_not_allowed_res),
+            ("optionsRes", &config.options_res),
+            ("timeoutRes", &config.timeout_res),
+        ];
+        for (key, response) in responses {
+            if response.as_ref().is_some_and(|res| res.trim().is_empty()) {
+                return Err((format!("config.{}", key), "must not be empty"));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn apply(self, convention: &mut Convention, config: &mut Config) {
+        fn set<T>(target: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+
+        let overrides = self.convention;
+        set(&mut convention.server_template, overrides.server_template);
+        set(&mut convention.app_dir_name, overrides.app_dir_name);
+        set(&mut convention.support_ext, overrides.support_ext);
+        set(
+            &mut convention.route_file_basename,
+            overrides.route_file_basename,
+        );
+        set(
+            &mut convention.middlewares_file_basename,
+            overrides.middlewares_file_basename,
+        );
+        set(
+            &mut convention.tail_middlewares_file_basename,
+            overrides.tail_middlewares_file_basename,
+        );
+        set(
+            &mut convention.settings_file_basename,
+            overrides.settings_file_basename,
+        );
+        set(
+            &mut convention.custom_server_basename,
+            overrides.custom_server_basename,
+        );
+        set(
+            &mut convention.settings_export_name,
+            overrides.settings_export_name,
+        );
+        set(
+            &mut convention.middlewares_export_name,
+            overrides.middlewares_export_name,
+        );
+        set(
+            &mut convention.tail_middlewares_export_name,
+            overrides.tail_middlewares_export_name,
+        );
+        set(
+            &mut convention.openapi_export_name,
+            overrides.openapi_export_name,
+        );
+        set(
+            &mut convention.config_export_name,
+            overrides.config_export_name,
+        );
+
+        let overrides = self.config;
+        set(&mut config.route_style, overrides.route_style);
+        set(&mut config.import_style, overrides.import_style);
+        set(
+            &mut config.method_not_allowed_res,
+            overrides.method_not_allowed_res,
+        );
+        set(&mut config.options_res, overrides.options_res);
+        set(&mut config.timeout_res, overrides.timeout_res);
+        set(&mut config.strict, overrides.strict);
+    }
+}
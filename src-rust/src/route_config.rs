@@ -0,0 +1,95 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::diagnostics::{Code, Diagnostic};
+use crate::methods::{FALLBACK_EXPORT, is_http_method};
+
+/// Maximum request body size, in bytes or as a size string understood by Express (`"10mb"`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BodyLimit {
+    Bytes(u64),
+    Size(String),
+}
+
+/// Statically evaluated `config` export of a route file.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct RouteConfig {
+    /// Limit of JSON and URL-encoded bodies, parsed before the top-level middlewares
+    pub body_limit: Option<BodyLimit>,
+    /// Respond with the timeout response when no response was sent after this many milliseconds
+    pub timeout_ms: Option<u64>,
+    /// Methods whose handlers are routed, every exported handler when unset
+    pub methods: Option<Vec<String>>,
+    /// Leave the route out of the server, the manifest and the OpenAPI document
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+impl RouteConfig {
+    pub(crate) fn parse(value: Option<&serde_json::Value>, route_abs_path: &Path) -> Result<Self> {
+        let Some(value) = value else {
+            return Ok(Self::default());
+        };
+        let invalid = |message: String| {
+            Diagnostic::error(
+                Code::InvalidRouteConfig,
+                format!("Invalid `config` export: {}", message),
+            )
+            .with_file(route_abs_path)
+        };
+        let route_config =
+            serde_json::from_value::<Self>(value.clone()).map_err(|e| invalid(e.to_string()))?;
+        if let Some(method) = route_config
+            .methods
+            .iter()
+            .flatten()
+            .find(|method| !is_http_method(method))
+        {
+            return Err(invalid(format!(
+                "`{}` in `methods` is not an uppercase HTTP method",
+                method
+            ))
+            .into());
+        }
+        if route_config.timeout_ms == Some(0) {
+            return Err(invalid("`timeoutMs` must be greater than 0".to_string()).into());
+        }
+        Ok(route_config)
+    }
+
+    /// Whether the exported handler of `method` is routed, the fallback always is.
+    pub(crate) fn routes_method(&self, method: &str) -> bool {
+        method == FALLBACK_EXPORT
+            || self
+                .methods
+                .as_ref()
+                .is_none_or(|methods| methods.iter().any(|m| m == method))
+    }
+
+    /// Generated middlewares applying the config, registered ahead of the top-level ones.
+    pub(crate) fn middlewares(&self, timeout_res: &str) -> Vec<String> {
+        let mut middlewares = Vec::new();
+        if let Some(timeout_ms) = self.timeout_ms {
+            middlewares.push(format!(
+                "(req, res, next) => {{ const timer = setTimeout(() => {{ if (!res.headersSent) {{ {} }} }}, {}); res.on(\"close\", () => clearTimeout(timer)); next(); }}",
+                timeout_res, timeout_ms
+            ));
+        }
+        if let Some(body_limit) = &self.body_limit {
+            // A body parsed here is skipped by the parsers of the top-level middlewares
+            let limit = match body_limit {
+                BodyLimit::Bytes(bytes) => bytes.to_string(),
+                BodyLimit::Size(size) => serde_json::Value::from(size.as_str()).to_string(),
+            };
+            middlewares.push(format!("express.json({{ limit: {} }})", limit));
+            middlewares.push(format!(
+                "express.urlencoded({{ extended: true, limit: {} }})",
+                limit
+            ));
+        }
+        middlewares
+    }
+}
@@ -0,0 +1,236 @@
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::methods::allowed_methods;
+use crate::{AppRoute, AppStruct, get_route_display_name, rel_path_to_endpoint};
+
+/// How the `routes` command prints the endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum RoutesFormat {
+    /// One aligned row per endpoint
+    #[default]
+    Table,
+    /// The app directory tree, with the endpoint of every route
+    Tree,
+    /// An array of endpoints
+    Json,
+}
+
+/// An endpoint the server exposes.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteEntry {
+    /// Full URL pattern from the app root, in Express path syntax
+    pub url: String,
+    /// Methods the endpoint responds to, as listed in its `Allow` header
+    pub methods: Vec<String>,
+    /// Middleware files, then the middleware exports of the route, outer to inner
+    pub middlewares: Vec<String>,
+    /// Route file, relative to the source directory
+    pub source: String,
+}
+
+/// A directory of the app tree, with the endpoint of its route file.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteNode {
+    /// Directory name, e.g. `[id]`
+    pub name: String,
+    /// Path relative to the source directory, e.g. `app/users/[id]`
+    pub path: String,
+    /// Middlewares file of the directory, relative to the source directory
+    pub middlewares: Option<String>,
+    /// `None` without a route file or when its config disables it
+    pub endpoint: Option<RouteEntry>,
+    pub children: Vec<RouteNode>,
+}
+
+/// Middleware exports of a route, e.g. `app/users/route.ts#POST_middlewares`, in the order
+/// the server applies them: the route-wide ones, then the per-method ones.
+pub(crate) fn route_middlewares(app_route: &AppRoute, source: &str) -> Vec<String> {
+    // Analysis drops `<METHOD>_middlewares` exports without a handler for their method
+    let is_method_middlewares = |middlewares: &str| {
+        app_route.exports.handlers.iter().any(|handler| {
+            middlewares
+                .strip_prefix(handler.export_name.as_str())
+                .is_some_and(|suffix| suffix.starts_with('_'))
+        })
+    };
+    let (route_wide, per_method): (Vec<_>, Vec<_>) = app_route
+        .exports
+        .middlewares
+        .iter()
+        .partition(|middlewares| !is_method_middlewares(middlewares));
+    route_wide
+        .into_iter()
+        .chain(per_method)
+        .map(|middlewares| format!("{}#{}", source, middlewares))
+        .collect()
+}
+
+/// The app tree of an analyzed app structure, in the order the server registers the routes.
+pub fn route_tree(app_struct: &AppStruct) -> Result<RouteNode> {
+    fn traverse(app_route: &AppRoute, middlewares: &mut Vec<String>) -> Result<RouteNode> {
+        let middlewares_file = app_route
+            .middlewares
+            .as_ref()
+            .map(|middlewares_file| format!("{}/{}", app_route.relative_path, middlewares_file));
+        if let Some(middlewares_file) = &middlewares_file {
+            middlewares.push(middlewares_file.clone());
+        }
+
+        let endpoint = match app_route.enabled_route() {
+            Some(route) => {
+                let source = format!("{}/{}", app_route.relative_path, route);
+                let mut chain = middlewares.clone();
+                chain.extend(route_middlewares(app_route, &source));
+                Some(RouteEntry {
+                    url: rel_path_to_endpoint(&source)?,
+                    methods: allowed_methods(&app_route.exports),
+                    middlewares: chain,
+                    source,
+                })
+            }
+            None => None,
+        };
+        let children = app_route
+            .children
+            .iter()
+            .map(|child| traverse(child, middlewares))
+            .collect::<Result<Vec<_>>>()?;
+
+        if middlewares_file.is_some() {
+            middlewares.pop();
+        }
+        Ok(RouteNode {
+            name: app_route.name.clone(),
+            path: app_route.relative_path.clone(),
+            middlewares: middlewares_file,
+            endpoint,
+            children,
+        })
+    }
+
+    let mut middlewares = Vec::new();
+    if let Some(top_level_middlewares) = &app_struct.top_level_middlewares {
+        middlewares.push(top_level_middlewares.clone());
+    }
+    traverse(&app_struct.app, &mut middlewares)
+}
+
+/// Every enabled endpoint, in the order the server registers them.
+pub fn collect_routes(app_struct: &AppStruct) -> Result<Vec<RouteEntry>> {
+    fn flatten(node: RouteNode, entries: &mut Vec<RouteEntry>) {
+        entries.extend(node.endpoint);
+        for child in node.children {
+            flatten(child, entries);
+        }
+    }
+
+    let mut entries = Vec::new();
+    flatten(route_tree(app_struct)?, &mut entries);
+    Ok(entries)
+}
+
+/// Aligned columns, the middleware chain last as it is the widest.
+fn render_table(entries: &[RouteEntry]) -> String {
+    let header = ["METHODS", "URL", "SOURCE", "MIDDLEWARES"].map(String::from);
+    let mut rows = vec![header];
+    for entry in entries {
+        let middlewares = if entry.middlewares.is_empty() {
+            "-".to_string()
+        } else {
+            entry.middlewares.join(" > ")
+        };
+        rows.push([
+            entry.methods.join(", "),
+            entry.url.clone(),
+            entry.source.clone(),
+            middlewares,
+        ]);
+    }
+
+    let mut widths = [0; 4];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let mut table = String::new();
+    for row in &rows {
+        let mut line = String::new();
+        for (cell, width) in row.iter().zip(widths) {
+            line.push_str(&format!("{:<width$}  ", cell, width = width));
+        }
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// The app directory tree, e.g. `:id/ -> /users/:id/ GET, HEAD, OPTIONS`, with the middleware
+/// files and exports of every directory in brackets.
+fn render_tree(app_struct: &AppStruct) -> Result<String> {
+    fn describe(app_route: &AppRoute, name: &str) -> Result<String> {
+        let mut line = format!("{}/", name);
+        let mut middlewares = Vec::new();
+        if let Some(middlewares_file) = &app_route.middlewares {
+            middlewares.push(middlewares_file.clone());
+        }
+        if let Some(route) = &app_route.route {
+            let source = format!("{}/{}", app_route.relative_path, route);
+            if app_route.enabled_route().is_some() {
+                line.push_str(&format!(
+                    " -> {} {}",
+                    rel_path_to_endpoint(&source)?,
+                    allowed_methods(&app_route.exports).join(", ")
+                ));
+                middlewares.extend(app_route.exports.middlewares.iter().cloned());
+            } else {
+                line.push_str(" (disabled)");
+            }
+        }
+        if !middlewares.is_empty() {
+            line.push_str(&format!(" [{}]", middlewares.join(", ")));
+        }
+        Ok(line)
+    }
+
+    fn traverse(app_route: &AppRoute, prefix: &str, tree: &mut String) -> Result<()> {
+        for (index, child) in app_route.children.iter().enumerate() {
+            let is_last = index + 1 == app_route.children.len();
+            let (branch, indent) = if is_last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            let line = describe(child, &get_route_display_name(&child.name))?;
+            tree.push_str(&format!("{}{}{}\n", prefix, branch, line));
+            traverse(child, &format!("{}{}", prefix, indent), tree)?;
+        }
+        Ok(())
+    }
+
+    let mut tree = String::new();
+    if let Some(top_level_middlewares) = &app_struct.top_level_middlewares {
+        tree.push_str(&format!("[{}]\n", top_level_middlewares));
+    }
+    tree.push_str(&describe(&app_struct.app, &app_struct.app.name)?);
+    tree.push('\n');
+    traverse(&app_struct.app, "", &mut tree)?;
+    Ok(tree)
+}
+
+/// The endpoints of an analyzed app structure, as printed by the `routes` command.
+pub fn render_routes(app_struct: &AppStruct, format: RoutesFormat) -> Result<String> {
+    Ok(match format {
+        RoutesFormat::Table => render_table(&collect_routes(app_struct)?),
+        RoutesFormat::Tree => render_tree(app_struct)?,
+        RoutesFormat::Json => {
+            let mut json = serde_json::to_string_pretty(&collect_routes(app_struct)?)
+                .context("Failed to serialize routes")?;
+            json.push('\n');
+            json
+        }
+    })
+}
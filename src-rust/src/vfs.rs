@@ -0,0 +1,206 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::RwLock;
+use walkdir::WalkDir;
+
+/// A file or directory found by [`FileSystem::walk`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    pub path: PathBuf,
+    pub is_dir: bool,
+}
+
+impl DirEntry {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Where the compiler reads the sources from and writes the server to.
+pub trait FileSystem: Send + Sync {
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()>;
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()>;
+
+    fn exists(&self, path: &Path) -> bool;
+
+    fn is_file(&self, path: &Path) -> bool;
+
+    /// Absolute path of an existing file or directory.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// `root` and everything below it, every directory before its content.
+    fn walk(&self, root: &Path) -> io::Result<Vec<DirEntry>>;
+}
+
+/// The real file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiskFs;
+
+impl FileSystem for DiskFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        std::fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        std::fs::write(path, contents)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        std::fs::create_dir_all(path)
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        path.is_file()
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        // WASI has no `canonicalize`, paths are only made absolute there
+        #[cfg(target_os = "wasi")]
+        return std::path::absolute(path).and_then(|path| std::fs::metadata(&path).map(|_| path));
+        #[cfg(not(target_os = "wasi"))]
+        path.canonicalize()
+    }
+
+    fn walk(&self, root: &Path) -> io::Result<Vec<DirEntry>> {
+        WalkDir::new(root)
+            .into_iter()
+            .map(|entry| {
+                let entry = entry?;
+                Ok(DirEntry {
+                    is_dir: entry.file_type().is_dir(),
+                    path: entry.into_path(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Files kept in memory, e.g. for the WebAssembly build. Relative paths are resolved against
+/// `/`, and directories exist as soon as a file is written below them.
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    /// File contents, `None` for directories
+    entries: RwLock<BTreeMap<PathBuf, Option<String>>>,
+}
+
+/// `path` made absolute against `/`, without `.` and `..` components.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::from("/");
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Prefix(_) | Component::RootDir | Component::CurDir => {}
+        }
+    }
+    normalized
+}
+
+fn not_found(path: &Path) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("{} does not exist", path.display()),
+    )
+}
+
+impl MemoryFs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A file system holding `files`, keyed by path.
+    pub fn from_files<P: AsRef<Path>, C: Into<String>>(
+        files: impl IntoIterator<Item = (P, C)>,
+    ) -> Self {
+        let fs = Self::new();
+        for (path, contents) in files {
+            fs.insert(path.as_ref(), contents.into());
+        }
+        fs
+    }
+
+    fn insert(&self, path: &Path, contents: String) {
+        let path = normalize(path);
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        for ancestor in path.ancestors().skip(1) {
+            entries.insert(ancestor.to_path_buf(), None);
+        }
+        entries.insert(path, Some(contents));
+    }
+}
+
+impl FileSystem for MemoryFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        match entries.get(&normalize(path)) {
+            Some(Some(contents)) => Ok(contents.clone()),
+            Some(None) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is a directory", path.display()),
+            )),
+            None => Err(not_found(path)),
+        }
+    }
+
+    fn write(&self, path: &Path, contents: &str) -> io::Result<()> {
+        self.insert(path, contents.to_string());
+        Ok(())
+    }
+
+    fn create_dir_all(&self, path: &Path) -> io::Result<()> {
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        for ancestor in normalize(path).ancestors() {
+            entries.entry(ancestor.to_path_buf()).or_insert(None);
+        }
+        Ok(())
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries.contains_key(&normalize(path))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        matches!(entries.get(&normalize(path)), Some(Some(_)))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        let normalized = normalize(path);
+        if self.exists(&normalized) {
+            Ok(normalized)
+        } else {
+            Err(not_found(path))
+        }
+    }
+
+    fn walk(&self, root: &Path) -> io::Result<Vec<DirEntry>> {
+        let root = normalize(root);
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        if !entries.contains_key(&root) {
+            return Err(not_found(&root));
+        }
+        // Paths compare component by component, so a directory sorts right before its content
+        Ok(entries
+            .range(root.clone()..)
+            .take_while(|(path, _)| path.starts_with(&root))
+            .map(|(path, contents)| DirEntry {
+                path: path.clone(),
+                is_dir: contents.is_none(),
+            })
+            .collect())
+    }
+}
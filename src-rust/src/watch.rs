@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use crate::cache::ExportsCache;
+use crate::exports::{RouteExports, get_endpoint_handlers};
+use crate::methods::is_handler_export;
+use crate::vfs::DiskFs;
+use crate::{
+    AppRoute, AppStruct, Config, Convention, RouteConfig, compile, route_middlewares_method,
+};
+
+/// Quiet period used to coalesce the burst of events produced by a single save.
+const DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// How a changed path relates to the compiled output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileRole {
+    Route,
+    CustomServer,
+    /// Middlewares, tail-middlewares and settings, only their existence matters
+    Imported,
+    /// A directory inside the app dir, or anything else whose removal may drop routes
+    AppTree,
+    Unrelated,
+}
+
+/// The parts of the exports of a route file that end up in the compiled output.
+#[derive(Debug, PartialEq)]
+struct OutputExports {
+    /// Routed handlers, as export name, whether they are async and their doc comment
+    handlers: Vec<(String, bool, Option<String>)>,
+    middlewares: Vec<String>,
+    config: Option<serde_json::Value>,
+    openapi: Option<serde_json::Value>,
+}
+
+struct WatchState<'a> {
+    src_path: PathBuf,
+    convention: &'a Convention,
+    /// Whether doc comments and `openapi` exports are written out, to an OpenAPI document
+    writes_openapi: bool,
+    /// Output-relevant exports of every compiled route file, keyed by absolute path
+    route_exports: HashMap<PathBuf, OutputExports>,
+    /// Modules that compiled routes re-export handlers from
+    dependencies: HashSet<PathBuf>,
+}
+
+impl WatchState<'_> {
+    fn file_role(&self, path: &Path) -> FileRole {
+        let Ok(relative_path) = path.strip_prefix(&self.src_path) else {
+            return FileRole::Unrelated;
+        };
+        let filename = path
+            .file_name()
+            .map(|f| f.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let is_top_level = relative_path.parent() == Some(Path::new(""));
+        let is_inside_app_dir = relative_path.starts_with(&self.convention.app_dir_name);
+
+        if is_top_level {
+            if self
+                .convention
+                .get_custom_server_filenames()
+                .contains(&filename)
+            {
+                return FileRole::CustomServer;
+            }
+            if self
+                .convention
+                .get_settings_filenames()
+                .iter()
+                .chain(&self.convention.get_middlewares_filenames())
+                .chain(&self.convention.get_tail_middlewares_filenames())
+                .any(|f| f == &filename)
+            {
+                return FileRole::Imported;
+            }
+        }
+        if !is_inside_app_dir {
+            return FileRole::Unrelated;
+        }
+        if self.convention.get_route_filenames().contains(&filename) {
+            return FileRole::Route;
+        }
+        if self
+            .convention
+            .get_middlewares_filenames()
+            .contains(&filename)
+        {
+            return FileRole::Imported;
+        }
+        let is_source_file = self
+            .convention
+            .support_ext
+            .iter()
+            .any(|ext| filename.ends_with(ext.as_str()));
+        if is_source_file && !path.is_dir() {
+            FileRole::Unrelated
+        } else {
+            FileRole::AppTree
+        }
+    }
+
+    /// Keep what the compiler would: routed handlers and the middlewares guarding them.
+    ///
+    /// `None` when the `config` export is invalid, which the compiler reports.
+    fn output_exports(&self, path: &Path, exports: &RouteExports) -> Option<OutputExports> {
+        let route_config = RouteConfig::parse(exports.config.as_ref(), path).ok()?;
+        let handlers: Vec<_> = exports
+            .handlers
+            .iter()
+            .filter(|handler| {
+                is_handler_export(&handler.export_name)
+                    && route_config.routes_method(&handler.export_name)
+            })
+            .map(|handler| {
+                let doc = handler.doc.clone().filter(|_| self.writes_openapi);
+                (handler.export_name.clone(), handler.is_async, doc)
+            })
+            .collect();
+        let middlewares = exports
+            .middlewares
+            .iter()
+            .filter(|middlewares| {
+                route_middlewares_method(middlewares, self.convention)
+                    .is_none_or(|method| handlers.iter().any(|(name, ..)| name == method))
+            })
+            .cloned()
+            .collect();
+        Some(OutputExports {
+            handlers,
+            middlewares,
+            config: exports.config.clone(),
+            openapi: exports.openapi.clone().filter(|_| self.writes_openapi),
+        })
+    }
+
+    fn exports_changed(&self, path: &Path) -> bool {
+        let Some(previous) = self.route_exports.get(path) else {
+            return true;
+        };
+        match get_endpoint_handlers(&DiskFs, path, self.convention) {
+            Ok(exports) => self.output_exports(path, &exports).as_ref() != Some(previous),
+            // Let the compiler report the error
+            Err(_) => true,
+        }
+    }
+
+    fn needs_recompile(&self, event: &Event) -> bool {
+        let is_structural = matches!(
+            event.kind,
+            EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+        );
+        let is_content = matches!(
+            event.kind,
+            EventKind::Modify(ModifyKind::Data(_) | ModifyKind::Any)
+        );
+
+        event.paths.iter().any(|path| {
+            if self.dependencies.contains(path) {
+                return is_structural || is_content;
+            }
+            let role = self.file_role(path);
+            let is_known_route = self.route_exports.contains_key(path) && path.exists();
+            match role {
+                FileRole::Unrelated => false,
+                // Editors often save by renaming a temp file over the original,
+                // which is a content change for a route that still exists
+                FileRole::Route if is_known_route => {
+                    (is_structural || is_content) && self.exports_changed(path)
+                }
+                // Routes that never compiled, e.g. created with a syntax error, have nothing
+                // to compare with
+                FileRole::Route | FileRole::CustomServer => is_structural || is_content,
+                FileRole::Imported | FileRole::AppTree => is_structural,
+            }
+        })
+    }
+
+    fn update_route_exports(&mut self, app_struct: &AppStruct) {
+        fn traverse(app_route: &AppRoute, state: &mut WatchState) {
+            if let Some(route) = &app_route.route {
+                let route_path = state.src_path.join(&app_route.relative_path).join(route);
+                if let Some(exports) = state.output_exports(&route_path, &app_route.exports) {
+                    state.route_exports.insert(route_path, exports);
+                }
+                state
+                    .dependencies
+                    .extend(app_route.exports.dependencies.iter().map(PathBuf::from));
+            }
+            for child in &app_route.children {
+                traverse(child, state);
+            }
+        }
+
+        self.route_exports.clear();
+        self.dependencies.clear();
+        traverse(&app_struct.app, self);
+    }
+}
+
+/// Compile once, then keep recompiling whenever a change under `src_dir` affects the output.
+///
+/// `on_build` runs after every compilation with the compiled app structure or the error, e.g.
+/// to report the diagnostics and refresh the manifest. Compile errors do not stop the watcher.
+/// Edits to doc comments and `openapi` exports only recompile when `writes_openapi` is set.
+#[allow(clippy::too_many_arguments)]
+pub fn watch<F>(
+    src_dir: &str,
+    dist_dir: &str,
+    filename: &str,
+    convention: &Convention,
+    config: &Config,
+    cache: &mut ExportsCache,
+    writes_openapi: bool,
+    mut on_build: F,
+) -> Result<()>
+where
+    F: FnMut(Result<&AppStruct>),
+{
+    let src_path = PathBuf::from(src_dir).canonicalize().map_err(|e| {
+        anyhow::anyhow!("Failed to canonicalize source directory {}: {}", src_dir, e)
+    })?;
+    let mut state = WatchState {
+        src_path,
+        convention,
+        writes_openapi,
+        route_exports: HashMap::new(),
+        dependencies: HashSet::new(),
+    };
+
+    let mut rebuild = |state: &mut WatchState| {
+        let start_time = Instant::now();
+        match compile(
+            &DiskFs, src_dir, dist_dir, filename, convention, config, cache,
+        ) {
+            Ok(app) => {
+                state.update_route_exports(&app);
+                log::info!("Compiled in {}ms", start_time.elapsed().as_millis());
+                on_build(Ok(&app));
+            }
+            Err(e) => {
+                // The exports of the failed build were not recorded, compare the next edits
+                // with nothing rather than with the last successful build
+                state.route_exports.clear();
+                on_build(Err(e));
+            }
+        }
+    };
+    rebuild(&mut state);
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).context("Failed to create file watcher")?;
+    watcher
+        .watch(&state.src_path, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", state.src_path.display()))?;
+    log::info!("Watching {} for changes", src_dir);
+
+    while let Ok(first) = rx.recv() {
+        let mut events = vec![first];
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            events.push(event);
+        }
+
+        let mut needs_recompile = false;
+        for event in events {
+            match event {
+                Ok(event) => {
+                    log::debug!("File event: {:?} {:?}", event.kind, event.paths);
+                    needs_recompile = needs_recompile || state.needs_recompile(&event);
+                }
+                Err(e) => log::warn!("File watcher error: {}", e),
+            }
+        }
+
+        if needs_recompile {
+            rebuild(&mut state);
+        } else {
+            log::debug!("No relevant changes, skip recompiling");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,565 @@
+//! Runs the CLI on the fixtures of `tests/fixtures` and compares what it writes with the
+//! expected files next to their `src` directory. Set `NEXP_UPDATE_SNAPSHOTS=1` to rewrite them.
+
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A fresh copy of fixture `name`, its `src` directory along with the project files next to it,
+/// returning the directory to run the CLI from. The generated server goes to its `dist`
+/// directory. Every call gets its own copy, so tests can share a fixture.
+fn fixture(name: &str) -> PathBuf {
+    fn copy_dir(from: &Path, to: &Path) {
+        fs::create_dir_all(to).unwrap();
+        for entry in fs::read_dir(from).unwrap() {
+            let entry = entry.unwrap();
+            let target = to.join(entry.file_name());
+            if entry.file_type().unwrap().is_dir() {
+                copy_dir(&entry.path(), &target);
+            } else {
+                fs::copy(entry.path(), target).unwrap();
+            }
+        }
+    }
+
+    static COPIES: AtomicUsize = AtomicUsize::new(0);
+    let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!(
+        "{}-{}",
+        name,
+        COPIES.fetch_add(1, Ordering::Relaxed)
+    ));
+    if dir.exists() {
+        fs::remove_dir_all(&dir).unwrap();
+    }
+    copy_dir(&fixture_dir(name), &dir);
+    fs::create_dir_all(dir.join("dist")).unwrap();
+    dir
+}
+
+fn fixture_dir(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+}
+
+fn nexp(dir: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_nexp-compiler-rs"))
+        .current_dir(dir)
+        .args(["--src-dir", "src", "--dist-dir", "dist"])
+        .args(args)
+        .env("NEXP_LOG", "off")
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8(output.stderr.clone()).unwrap()
+}
+
+fn read(dir: &Path, file: &str) -> String {
+    fs::read_to_string(dir.join(file)).unwrap()
+}
+
+/// Compare `actual` with the expected file `file` of fixture `name`.
+fn assert_snapshot(name: &str, file: &str, actual: &str) {
+    let path = fixture_dir(name).join(file);
+    if std::env::var_os("NEXP_UPDATE_SNAPSHOTS").is_some() {
+        fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("Failed to read snapshot {}: {}", path.display(), e));
+    assert_eq!(actual, expected, "{} differs", path.display());
+}
+
+#[test]
+fn openapi_document() {
+    let dir = fixture("todos");
+    let output = nexp(&dir, &["--openapi", "dist/openapi.json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("todos", "openapi.json", &read(&dir, "dist/openapi.json"));
+}
+
+#[test]
+fn openapi_export_must_be_an_object() {
+    let dir = fixture("todos");
+    fs::create_dir_all(dir.join("src/app/health")).unwrap();
+    fs::write(
+        dir.join("src/app/health/route.ts"),
+        "export const openapi = 'Health check';\nexport const GET = (req, res) => res.end();\n",
+    )
+    .unwrap();
+    let output = nexp(
+        &dir,
+        &["--openapi", "dist/openapi.json", "--message-format", "json"],
+    );
+    assert!(output.status.success(), "{}", stderr(&output));
+    let warning: serde_json::Value = serde_json::from_str(stderr(&output).trim()).unwrap();
+    assert_eq!(warning["code"], "NEXP008");
+    assert_eq!(warning["severity"], "warning");
+    let document: serde_json::Value =
+        serde_json::from_str(&read(&dir, "dist/openapi.json")).unwrap();
+    assert!(document["paths"]["/health"]["get"].is_object());
+
+    let output = nexp(&dir, &["--strict"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("NEXP008"), "{}", stderr(&output));
+}
+
+#[test]
+fn route_manifest() {
+    let dir = fixture("todos");
+    let output = nexp(&dir, &["--manifest", "dist/manifest.json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("todos", "manifest.json", &read(&dir, "dist/manifest.json"));
+}
+
+#[test]
+fn cache_invalidates_edited_routes() {
+    let dir = fixture("todos");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(dir.join("dist/.nexp-cache").exists());
+    let cold = read(&dir, "dist/server.ts");
+
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_eq!(read(&dir, "dist/server.ts"), cold);
+
+    let route = dir.join("src/app/route.ts");
+    let source = fs::read_to_string(&route).unwrap();
+    fs::write(
+        &route,
+        format!("{}\nexport const POST = (req, res) => res.end();\n", source),
+    )
+    .unwrap();
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let server = read(&dir, "dist/server.ts");
+    assert!(
+        server.contains("POST as app_POST"),
+        "POST is missing from:\n{}",
+        server
+    );
+}
+
+/// Add `count` routes below `app/generated` of the fixture copy `dir`, with the given source.
+fn generate_routes(dir: &Path, count: usize, source: &str) {
+    for i in 0..count {
+        let route_dir = dir.join(format!("src/app/generated/route-{:02}", i));
+        fs::create_dir_all(&route_dir).unwrap();
+        fs::write(route_dir.join("route.ts"), source).unwrap();
+    }
+}
+
+#[test]
+fn parallel_analysis_is_deterministic() {
+    let dir = fixture("todos");
+    generate_routes(&dir, 48, "export const GET = (req, res) => res.end();\n");
+    let mut servers = Vec::new();
+    for _ in 0..4 {
+        let output = nexp(&dir, &["--no-cache", "--manifest", "dist/manifest.json"]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        servers.push((
+            read(&dir, "dist/server.ts"),
+            read(&dir, "dist/manifest.json"),
+        ));
+    }
+    assert!(servers.windows(2).all(|pair| pair[0] == pair[1]));
+
+    // Every run fails on the same broken route, the first one in traversal order
+    generate_routes(&dir, 48, "export const schema = {};\n");
+    let errors = (0..4)
+        .map(|_| {
+            let output = nexp(&dir, &["--no-cache"]);
+            assert!(!output.status.success());
+            stderr(&output)
+        })
+        .collect::<Vec<_>>();
+    assert!(errors[0].contains("route-00"), "{}", errors[0]);
+    assert!(errors.windows(2).all(|pair| pair[0] == pair[1]));
+}
+
+#[test]
+fn warnings_are_ordered_and_cached() {
+    let dir = fixture("todos");
+    generate_routes(
+        &dir,
+        16,
+        "export const GET = (req, res) => res.end();\nexport const openapi = describe();\n",
+    );
+    let cold = nexp(&dir, &["--message-format", "json"]);
+    assert!(cold.status.success(), "{}", stderr(&cold));
+    let warnings = stderr(&cold);
+    let positions = (0..16)
+        .map(|i| warnings.find(&format!("route-{:02}", i)))
+        .collect::<Option<Vec<_>>>()
+        .unwrap_or_else(|| panic!("a warning is missing from:\n{}", warnings));
+    assert!(positions.is_sorted(), "{}", warnings);
+
+    // Cached routes are not parsed again, their warnings are still reported
+    let warm = nexp(&dir, &["--message-format", "json"]);
+    assert!(warm.status.success(), "{}", stderr(&warm));
+    assert_eq!(stderr(&warm), warnings);
+}
+
+#[test]
+fn import_styles() {
+    for (import_style, file) in [
+        ("node-esm", "server-node-esm.ts"),
+        ("commonjs", "server-commonjs.ts"),
+    ] {
+        let dir = fixture("reexports");
+        let output = nexp(&dir, &["--import-style", import_style]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_snapshot("reexports", file, &read(&dir, "dist/server.ts"));
+    }
+}
+
+#[test]
+fn reexported_handlers() {
+    let dir = fixture("reexports");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("reexports", "server.ts", &read(&dir, "dist/server.ts"));
+}
+
+#[test]
+fn cache_follows_reexported_modules() {
+    let dir = fixture("reexports");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    // Only a module the route re-exports from changes, not the route itself
+    let admin = dir.join("src/handlers/admin.ts");
+    let source = fs::read_to_string(&admin).unwrap();
+    fs::write(
+        &admin,
+        format!(
+            "{}\nexport const PATCH = (req, res) => res.end();\n",
+            source
+        ),
+    )
+    .unwrap();
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let server = read(&dir, "dist/server.ts");
+    assert!(
+        server.contains("PATCH as app_v2_users_PATCH"),
+        "PATCH is missing from:\n{}",
+        server
+    );
+}
+
+#[test]
+fn cache_follows_unresolved_modules() {
+    let dir = fixture("reexports");
+    let route = dir.join("src/app/v2/audit/route.ts");
+    fs::create_dir_all(route.parent().unwrap()).unwrap();
+    fs::write(
+        &route,
+        "export const POST = (req, res) => res.end();\nexport * from \"../../../handlers/audit\";\n",
+    )
+    .unwrap();
+    let output = nexp(&dir, &["--message-format", "json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stderr(&output).contains("NEXP006"), "{}", stderr(&output));
+
+    // The route is untouched, only the module it re-exports from appears
+    fs::write(
+        dir.join("src/handlers/audit.ts"),
+        "export const GET = (req, res) => res.end();\n",
+    )
+    .unwrap();
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    let server = read(&dir, "dist/server.ts");
+    assert!(
+        server.contains("GET as app_v2_audit_GET"),
+        "GET is missing from:\n{}",
+        server
+    );
+}
+
+#[test]
+fn strict_fails_on_suspicious_exports() {
+    let dir = fixture("strict");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(dir.join("dist/server.ts").exists());
+
+    let dir = fixture("strict");
+    let output = nexp(&dir, &["--strict"]);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("`post`"), "{}", stderr(&output));
+    assert!(!stderr(&output).contains("`search`"), "{}", stderr(&output));
+    assert!(!dir.join("dist/server.ts").exists());
+}
+
+#[test]
+fn route_config_before_top_level_middlewares() {
+    // A route body parser with a larger limit must run before the global `express.json()`
+    let dir = fixture("route-config");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("route-config", "server.ts", &read(&dir, "dist/server.ts"));
+}
+
+#[test]
+fn catch_all_segments() {
+    // Static siblings come first, then dynamic segments, then the catch-alls
+    let dir = fixture("catch-all");
+    let output = nexp(&dir, &[]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("catch-all", "server.ts", &read(&dir, "dist/server.ts"));
+    let output = nexp(&dir, &["routes"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("catch-all", "routes.txt", &stdout(&output));
+
+    // Nothing can follow a catch-all segment
+    let dir = fixture("catch-all");
+    fs::create_dir_all(dir.join("src/app/files/[...path]/raw")).unwrap();
+    fs::write(
+        dir.join("src/app/files/[...path]/raw/route.ts"),
+        "export const GET = (req, res) => res.end();\n",
+    )
+    .unwrap();
+    let output = nexp(&dir, &[]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("Catch-all segment must be the last segment of a route"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn native_route_style() {
+    let dir = fixture("todos");
+    let output = nexp(&dir, &["--route-style", "native"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("todos", "server-native.ts", &read(&dir, "dist/server.ts"));
+}
+
+#[test]
+fn project_config_next_to_src_dir() {
+    let dir = fixture("todos");
+    fs::write(
+        dir.join("nexp.config.json"),
+        r#"{ "config": { "routeStyle": "native" } }"#,
+    )
+    .unwrap();
+    // Run from another directory, the config is looked up in the parent of the source directory
+    let output = Command::new(env!("CARGO_BIN_EXE_nexp-compiler-rs"))
+        .current_dir(env!("CARGO_TARGET_TMPDIR"))
+        .arg("--src-dir")
+        .arg(dir.join("src"))
+        .arg("--dist-dir")
+        .arg(dir.join("dist"))
+        .env("NEXP_LOG", "off")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("todos", "server-native.ts", &read(&dir, "dist/server.ts"));
+}
+
+#[test]
+fn project_config_errors() {
+    let cases = [
+        (
+            r#"{ "config": { "routeStlye": "native" } }"#,
+            "unknown field `routeStlye`",
+        ),
+        (
+            r#"{ "config": { "importStyle": "amd" } }"#,
+            "Invalid project config at `config.importStyle`",
+        ),
+        (
+            r#"{ "convention": { "appDirName": "app/api" } }"#,
+            "`convention.appDirName`: must be a non-empty file or directory name",
+        ),
+        (
+            r#"{ "convention": { "supportExt": ["ts"] } }"#,
+            "`convention.supportExt[0]`: must be an extension starting with a dot",
+        ),
+        (
+            r#"{ "convention": { "configExportName": "route-config" } }"#,
+            "`convention.configExportName`: must be a valid JavaScript identifier",
+        ),
+    ];
+    for (config, message) in cases {
+        let dir = fixture("todos");
+        fs::write(dir.join("nexp.config.json"), config).unwrap();
+        let output = nexp(&dir, &[]);
+        assert!(!output.status.success(), "{} was accepted", config);
+        let stderr = stderr(&output);
+        assert!(stderr.contains("NEXP007"), "{}", stderr);
+        assert!(stderr.contains(message), "{}", stderr);
+        assert!(!dir.join("dist/server.ts").exists());
+    }
+
+    // An explicit config file must exist
+    let dir = fixture("todos");
+    let output = nexp(&dir, &["--config", "missing.json"]);
+    assert!(!output.status.success());
+    assert!(
+        stderr(&output).contains("missing.json"),
+        "{}",
+        stderr(&output)
+    );
+}
+
+#[test]
+fn conflicting_routes() {
+    let dir = fixture("conflicts");
+    let output = nexp(&dir, &["--message-format", "json"]);
+    assert!(!output.status.success());
+    assert_snapshot("conflicts", "errors.jsonl", &stderr(&output));
+    assert!(!dir.join("dist/server.ts").exists());
+}
+
+#[test]
+fn collision_free_identifiers() {
+    // Directories whose names escape to the same identifiers, and names outside of ASCII
+    let dir = fixture("collisions");
+    let output = nexp(&dir, &["--route-style", "native"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert_snapshot("collisions", "server.ts", &read(&dir, "dist/server.ts"));
+}
+
+#[test]
+fn json_diagnostics() {
+    let dir = fixture("strict");
+    let output = nexp(&dir, &["--message-format", "json"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(output.stdout.is_empty());
+    assert_snapshot("strict", "warnings.jsonl", &stderr(&output));
+
+    let dir = fixture("strict");
+    let output = nexp(&dir, &["--message-format", "json", "--strict"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_snapshot("strict", "errors.jsonl", &stderr(&output));
+
+    // One object per line, located in the source file
+    let dir = fixture("strict");
+    fs::create_dir_all(dir.join("src/app/broken")).unwrap();
+    fs::write(
+        dir.join("src/app/broken/route.ts"),
+        "export const GET = (req, res) => {\n  res.end(;\n};\n",
+    )
+    .unwrap();
+    let output = nexp(&dir, &["--message-format", "json"]);
+    assert!(!output.status.success());
+    let diagnostics = stderr(&output)
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .collect::<Vec<_>>();
+    let error = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic["severity"] == "error")
+        .unwrap_or_else(|| panic!("no error in {:?}", diagnostics));
+    assert_eq!(error["code"], "NEXP001");
+    assert_eq!(error["category"], "parse-error");
+    assert_eq!(error["file"], "src/app/broken/route.ts");
+    assert_eq!(error["line"], 2);
+    assert!(
+        error["rendered"]
+            .as_str()
+            .unwrap()
+            .contains("|   res.end(;"),
+        "{}",
+        error["rendered"]
+    );
+}
+
+#[test]
+fn routes_formats() {
+    let dir = fixture("todos");
+    fs::remove_dir(dir.join("dist")).unwrap();
+    for (format, file) in [
+        ("table", "routes.txt"),
+        ("tree", "routes-tree.txt"),
+        ("json", "routes.json"),
+    ] {
+        let output = nexp(&dir, &["routes", "--format", format]);
+        assert!(output.status.success(), "{}", stderr(&output));
+        assert_snapshot("todos", file, &stdout(&output));
+    }
+    // Routes are only listed, nothing is written, not even the dist directory
+    assert!(!dir.join("dist").exists());
+}
+
+/// Poll `condition` until it holds, failing after 10 seconds.
+fn wait_for(what: &str, mut condition: impl FnMut() -> bool) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "Timed out waiting for {}",
+            what
+        );
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn watch_recovers_from_failed_builds() {
+    let dir = fixture("todos");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_nexp-compiler-rs"))
+        .current_dir(&dir)
+        .args(["--src-dir", "src", "--dist-dir", "dist", "--watch"])
+        // The watcher logs when it is ready
+        .env("NEXP_LOG", "info")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Everything printed so far, both streams interleaved
+    let printed = Arc::new(Mutex::new(String::new()));
+    let mut readers: Vec<Box<dyn Read + Send>> = vec![
+        Box::new(child.stdout.take().unwrap()),
+        Box::new(child.stderr.take().unwrap()),
+    ];
+    for mut reader in readers.drain(..) {
+        let printed = Arc::clone(&printed);
+        thread::spawn(move || {
+            let mut buffer = [0; 1024];
+            while let Ok(count @ 1..) = reader.read(&mut buffer) {
+                let chunk = String::from_utf8_lossy(&buffer[..count]);
+                printed.lock().unwrap().push_str(&chunk);
+            }
+        });
+    }
+    let printed_count = |text: &str| printed.lock().unwrap().matches(text).count();
+    let server = || fs::read_to_string(dir.join("dist/server.ts")).unwrap_or_default();
+    wait_for("the watcher", || printed_count("Watching") == 1);
+
+    // A route created broken never compiles, the edit fixing it must still rebuild
+    let route = dir.join("src/app/fresh/route.ts");
+    fs::create_dir_all(route.parent().unwrap()).unwrap();
+    fs::write(&route, "export const GET = (;\n").unwrap();
+    wait_for("the failed build", || printed_count("NEXP001") == 1);
+    fs::write(&route, "export const GET = (req, res) => res.end();\n").unwrap();
+    wait_for("the fixed route", || server().contains("app_fresh_GET"));
+
+    // Then edits compare with the exports of the fixed build
+    fs::write(
+        &route,
+        "export const GET = (req, res) => res.end();\nexport const POST = (req, res) => res.end();\n",
+    )
+    .unwrap();
+    wait_for("the edited route", || server().contains("app_fresh_POST"));
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
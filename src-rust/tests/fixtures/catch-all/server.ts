@@ -0,0 +1,52 @@
+import express from "express";
+import { GET as app_docs_slug_GET } from "../src/app/docs/[[...slug]]/route";
+import { GET as app_files_GET } from "../src/app/files/route";
+import { GET as app_files_recent_GET } from "../src/app/files/recent/route";
+import { GET as app_files_id_GET } from "../src/app/files/[id]/route";
+import { DELETE as app_files_id_DELETE } from "../src/app/files/[id]/route";
+import { GET as app_files_path_GET } from "../src/app/files/[...path]/route";
+
+
+export const createServer = () => {
+  const app = express();
+
+  
+
+  
+
+  // ===== routes [{*slug} | app/docs/{*slug}] =====
+app.all("/docs{/*slug}", async (req, res, next) => { try { if (req.method === "GET") { await app_docs_slug_GET(req, res, next); return; }
+if (req.method === "HEAD") { await app_docs_slug_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [files | app/files] =====
+app.all("/files/", async (req, res, next) => { try { if (req.method === "GET") { await app_files_GET(req, res, next); return; }
+if (req.method === "HEAD") { await app_files_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [recent | app/files/recent] =====
+app.all("/files/recent/", async (req, res, next) => { try { if (req.method === "GET") { await app_files_recent_GET(req, res, next); return; }
+if (req.method === "HEAD") { await app_files_recent_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [:id | app/files/:id] =====
+app.all("/files/:id/", async (req, res, next) => { try { if (req.method === "GET") { await app_files_id_GET(req, res, next); return; }
+if (req.method === "DELETE") { await app_files_id_DELETE(req, res, next); return; }
+if (req.method === "HEAD") { await app_files_id_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [*path | app/files/*path] =====
+app.all("/files/*path", async (req, res, next) => { try { if (req.method === "GET") { await app_files_path_GET(req, res, next); return; }
+if (req.method === "HEAD") { await app_files_path_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+
+
+  
+  return app;
+};
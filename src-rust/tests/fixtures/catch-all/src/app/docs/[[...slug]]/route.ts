@@ -0,0 +1,5 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const GET: RouteHandler = async (req, res) => {
+  res.send(`Docs for ${(req.params.slug ?? []).join("/") || "index"}`);
+};
@@ -0,0 +1,9 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const GET: RouteHandler = async (req, res) => {
+  res.json({ id: req.params.id });
+};
+
+export const DELETE: RouteHandler = async (req, res) => {
+  res.status(204).end();
+};
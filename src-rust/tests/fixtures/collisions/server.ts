@@ -0,0 +1,59 @@
+import express from "express";
+import { middlewares as user_listMiddlewares } from "../src/app/user-list/middlewares";
+import { GET as app_user_list_GET } from "../src/app/user-list/route";
+import { middlewares as user_listMiddlewares_2 } from "../src/app/user_list/middlewares";
+import { GET as app_user_list_GET_2 } from "../src/app/user_list/route";
+import { GET as app_v1_0_class_GET } from "../src/app/v1.0/class/route";
+import { GET as app_v1_0_class_GET_2 } from "../src/app/v1_0/class/route";
+import { GET as app_u7528u6237_GET } from "../src/app/用户/route";
+import { GET as app_u8ba2u5355_GET } from "../src/app/订单/route";
+
+
+export const createServer = () => {
+  const app = express();
+
+  
+
+  
+
+  // ===== routes [user-list | app/user-list] =====
+const user_listRouter = express.Router();
+app.use("/user-list", user_listRouter);
+user_listRouter.use(...user_listMiddlewares);
+user_listRouter.get("/", async (req, res, next) => { try { await app_user_list_GET(req, res, next); } catch (err) { next(err); } });
+user_listRouter.all("/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [user_list | app/user_list] =====
+const user_listRouter_2 = express.Router();
+app.use("/user_list", user_listRouter_2);
+user_listRouter_2.use(...user_listMiddlewares_2);
+user_listRouter_2.get("/", async (req, res, next) => { try { await app_user_list_GET_2(req, res, next); } catch (err) { next(err); } });
+user_listRouter_2.all("/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [class | app/v1.0/class] =====
+app.get("/v1.0/class/", async (req, res, next) => { try { await app_v1_0_class_GET(req, res, next); } catch (err) { next(err); } });
+app.all("/v1.0/class/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [class | app/v1_0/class] =====
+app.get("/v1_0/class/", async (req, res, next) => { try { await app_v1_0_class_GET_2(req, res, next); } catch (err) { next(err); } });
+app.all("/v1_0/class/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [用户 | app/用户] =====
+app.get("/用户/", async (req, res, next) => { try { await app_u7528u6237_GET(req, res, next); } catch (err) { next(err); } });
+app.all("/用户/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [订单 | app/订单] =====
+app.get("/订单/", async (req, res, next) => { try { await app_u8ba2u5355_GET(req, res, next); } catch (err) { next(err); } });
+app.all("/订单/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+
+
+  
+  return app;
+};
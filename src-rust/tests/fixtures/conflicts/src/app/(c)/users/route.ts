@@ -0,0 +1,4 @@
+// Disabled routes never conflict
+export const config = { disabled: true };
+
+export const GET = (req, res) => res.end();
@@ -0,0 +1,33 @@
+import express from "express";
+import { GET as app_v1_users_GET } from "../src/app/v1/users/route";
+import { POST as app_v1_users_POST } from "../src/app/v1/users/route";
+import { GET as app_v2_users_GET } from "../src/app/v2/users/route";
+import { DELETE as app_v2_users_DELETE } from "../src/app/v2/users/route";
+
+
+export const createServer = () => {
+  const app = express();
+
+  
+
+  
+
+  // ===== routes [users | app/v1/users] =====
+app.all("/v1/users/", async (req, res, next) => { try { if (req.method === "GET") { await app_v1_users_GET(req, res, next); return; }
+if (req.method === "POST") { await app_v1_users_POST(req, res, next); return; }
+if (req.method === "HEAD") { await app_v1_users_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, POST, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, POST, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [users | app/v2/users] =====
+app.all("/v2/users/", async (req, res, next) => { try { if (req.method === "GET") { await app_v2_users_GET(req, res, next); return; }
+if (req.method === "DELETE") { await app_v2_users_DELETE(req, res, next); return; }
+if (req.method === "HEAD") { await app_v2_users_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+
+
+  
+  return app;
+};
@@ -0,0 +1,4 @@
+import { listUsers } from "../../../handlers/users";
+
+export { listUsers as GET };
+export { createUser as POST } from "../../../handlers/users";
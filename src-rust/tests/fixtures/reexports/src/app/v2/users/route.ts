@@ -0,0 +1,2 @@
+export { GET } from "../../v1/users/route";
+export * from "../../../handlers/admin";
@@ -0,0 +1,9 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const DELETE: RouteHandler = (req, res) => {
+  res.status(204).end();
+};
+
+export default (req, res) => {
+  res.status(404).end();
+};
@@ -0,0 +1,9 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const listUsers: RouteHandler = (req, res) => {
+  res.json([]);
+};
+
+export const createUser: RouteHandler = (req, res) => {
+  res.status(201).json(req.body);
+};
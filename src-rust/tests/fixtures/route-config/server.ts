@@ -0,0 +1,31 @@
+import express from "express";
+import { middlewares as topLevelMiddlewares } from "../src/middlewares";
+import { GET as app_profile_GET } from "../src/app/profile/route";
+import { POST as app_upload_POST } from "../src/app/upload/route";
+
+
+export const createServer = () => {
+  const app = express();
+
+  
+
+  app.all("/upload/", (req, res, next) => { const timer = setTimeout(() => { if (!res.headersSent) { res.status(503).send("Service Unavailable"); } }, 30000); res.on("close", () => clearTimeout(timer)); next(); }, express.json({ limit: "10mb" }), express.urlencoded({ extended: true, limit: "10mb" }));
+app.use(...topLevelMiddlewares);
+
+
+  // ===== routes [profile | app/profile] =====
+app.all("/profile/", async (req, res, next) => { try { if (req.method === "GET") { await app_profile_GET(req, res, next); return; }
+if (req.method === "HEAD") { await app_profile_GET(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [upload | app/upload] =====
+app.all("/upload/", async (req, res, next) => { try { if (req.method === "POST") { await app_upload_POST(req, res, next); return; }
+if (req.method === "OPTIONS") { res.set("Allow", "POST, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "POST, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+
+
+  
+  return app;
+};
@@ -0,0 +1,10 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const config = {
+  bodyLimit: "10mb",
+  timeoutMs: 30_000,
+};
+
+export const POST: RouteHandler = async (req, res) => {
+  res.status(201).json({ size: JSON.stringify(req.body).length });
+};
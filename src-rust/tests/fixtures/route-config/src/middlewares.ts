@@ -0,0 +1,12 @@
+import express from "express";
+import { RequestHandler } from "express";
+
+const authenticate: RequestHandler = (req, res, next) => {
+  if (!req.headers.authorization) {
+    res.status(401).end();
+    return;
+  }
+  next();
+};
+
+export const middlewares = [express.json(), authenticate];
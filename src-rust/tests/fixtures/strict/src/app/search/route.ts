@@ -0,0 +1,10 @@
+import { RouteHandler } from "@qordli/next-express";
+
+// Helpers named after rare HTTP methods are not mistaken for misnamed handlers
+export const search = (query: string) => query.trim();
+export const link = "/search";
+export const copy = <T>(value: T) => structuredClone(value);
+
+export const GET: RouteHandler = (req, res) => {
+  res.json([search(String(req.query.q ?? ""))]);
+};
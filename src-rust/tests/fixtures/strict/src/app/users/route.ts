@@ -0,0 +1,11 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const GET: RouteHandler = (req, res) => {
+  res.json([]);
+};
+
+export const post: RouteHandler = (req, res) => {
+  res.status(201).end();
+};
+
+export const PUT_middlewares = [];
@@ -0,0 +1,52 @@
+import express from "express";
+import { middlewares as topLevelMiddlewares } from "../src/middlewares";
+import { GET as app_GET } from "../src/app/route";
+import { HEAD as app_status_HEAD } from "../src/app/status/route";
+import { GET as app_status_GET } from "../src/app/status/route";
+import { middlewares as todosMiddlewares } from "../src/app/todos/middlewares";
+import { GET as app_todos_GET } from "../src/app/todos/route";
+import { POST as app_todos_POST } from "../src/app/todos/route";
+import { DELETE_middlewares as app_todos_id_DELETE_middlewares } from "../src/app/todos/[id]/route";
+import { GET as app_todos_id_GET } from "../src/app/todos/[id]/route";
+import { DELETE as app_todos_id_DELETE } from "../src/app/todos/[id]/route";
+
+
+export const createServer = () => {
+  const app = express();
+
+  
+
+  app.use(...topLevelMiddlewares);
+
+
+  // ===== routes [app | app] =====
+app.get("/", async (req, res, next) => { try { await app_GET(req, res, next); } catch (err) { next(err); } });
+app.all("/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [status | app/status] =====
+app.head("/status/", async (req, res, next) => { try { await app_status_HEAD(req, res, next); } catch (err) { next(err); } });
+app.get("/status/", async (req, res, next) => { try { await app_status_GET(req, res, next); } catch (err) { next(err); } });
+app.all("/status/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [todos | app/todos] =====
+const todosRouter = express.Router();
+app.use("/todos", todosRouter);
+todosRouter.use(...todosMiddlewares);
+todosRouter.get("/", async (req, res, next) => { try { await app_todos_GET(req, res, next); } catch (err) { next(err); } });
+todosRouter.post("/", async (req, res, next) => { try { await app_todos_POST(req, res, next); } catch (err) { next(err); } });
+todosRouter.all("/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, POST, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, POST, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+// ===== routes [:id | app/todos/:id] =====
+todosRouter.get("/:id/", async (req, res, next) => { try { await app_todos_id_GET(req, res, next); } catch (err) { next(err); } });
+todosRouter.delete("/:id/", ...app_todos_id_DELETE_middlewares, async (req, res, next) => { try { await app_todos_id_DELETE(req, res, next); } catch (err) { next(err); } });
+todosRouter.all("/:id/", async (req, res, next) => { try { if (req.method === "OPTIONS") { res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(204).end(); return; }
+ res.set("Allow", "GET, DELETE, HEAD, OPTIONS"); res.status(405).send(`Method ${req.method} Not Allowed`); } catch (err) { next(err); } });
+
+
+
+  
+  return app;
+};
@@ -0,0 +1,6 @@
+import { RouteHandler } from "@qordli/next-express";
+
+/** Health check */
+export const GET: RouteHandler = (req, res) => {
+  res.send("ok");
+};
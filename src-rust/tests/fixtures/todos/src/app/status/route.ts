@@ -0,0 +1,10 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const GET: RouteHandler = (req, res) => {
+  res.json({ ok: true });
+};
+
+// Exported after GET, which Express would otherwise answer HEAD requests with
+export const HEAD: RouteHandler = (req, res) => {
+  res.end();
+};
@@ -0,0 +1,20 @@
+import { RouteHandler } from "@qordli/next-express";
+import { RequestHandler } from "express";
+
+const requireAdmin: RequestHandler = (req, res, next) => next();
+
+/** Get a todo */
+export const GET: RouteHandler = async (req, res) => {
+  res.json({ id: req.params.id });
+};
+
+/**
+ * Delete a todo
+ *
+ * @deprecated archive it instead
+ */
+export const DELETE: RouteHandler = async (req, res) => {
+  res.status(204).end();
+};
+
+export const DELETE_middlewares = [requireAdmin];
@@ -0,0 +1,11 @@
+import { RequestHandler } from "express";
+
+const auth: RequestHandler = (req, res, next) => {
+  if (!req.headers.authorization) {
+    res.status(401).end();
+    return;
+  }
+  next();
+};
+
+export const middlewares = [auth];
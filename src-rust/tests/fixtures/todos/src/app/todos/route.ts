@@ -0,0 +1,21 @@
+import { RouteHandler } from "@qordli/next-express";
+
+/**
+ * List todos
+ *
+ * Every todo of the authenticated user, oldest first.
+ */
+export const GET: RouteHandler = async (req, res) => {
+  res.json([]);
+};
+
+/** Create a todo */
+export const POST: RouteHandler = async (req, res) => {
+  res.status(201).json(req.body);
+};
+
+export const openapi = {
+  summary: "Todos of the user",
+  GET: { tags: ["todos"] },
+  POST: { tags: ["todos"], responses: { "201": { description: "Created" } } },
+};
@@ -0,0 +1,155 @@
+//! Runs the compiler on sources held in a `MemoryFs`, without any temporary directory.
+
+use std::path::Path;
+
+use nexp_compiler_rs::cache::ExportsCache;
+use nexp_compiler_rs::diagnostics::{Diagnostic, Severity};
+use nexp_compiler_rs::manifest::build_manifest;
+use nexp_compiler_rs::vfs::{FileSystem, MemoryFs};
+use nexp_compiler_rs::{AppRoute, Config, Convention, analyze, compile, scan};
+
+/// A file system holding `files` and an empty `dist` directory.
+fn memory_fs(files: &[(&str, &str)]) -> MemoryFs {
+    let fs = MemoryFs::from_files(files.iter().copied());
+    fs.create_dir_all(Path::new("dist")).unwrap();
+    fs
+}
+
+fn child<'a>(app_route: &'a AppRoute, name: &str) -> &'a AppRoute {
+    app_route
+        .children
+        .iter()
+        .find(|child| child.name == name)
+        .unwrap_or_else(|| panic!("{} has no child {}", app_route.relative_path, name))
+}
+
+#[test]
+fn scan_route_tree() {
+    let fs = memory_fs(&[
+        ("src/middlewares.ts", "export default [];"),
+        (
+            "src/app/route.ts",
+            "export const GET = (req, res) => res.end();",
+        ),
+        (
+            "src/app/todos/route.ts",
+            "export const GET = (req, res) => res.end();",
+        ),
+        ("src/app/todos/middlewares.ts", "export default [];"),
+        (
+            "src/app/todos/[id]/route.ts",
+            "export const GET = (req, res) => res.end();",
+        ),
+        (
+            "src/app/(auth)/signin/route.ts",
+            "export const POST = (req, res) => res.end();",
+        ),
+    ]);
+    let app_struct = scan(&fs, "src", "dist", &Convention::default()).unwrap();
+
+    assert_eq!(
+        app_struct.top_level_middlewares.as_deref(),
+        Some("middlewares.ts")
+    );
+    assert_eq!(app_struct.app.route.as_deref(), Some("route.ts"));
+    let todos = child(&app_struct.app, "todos");
+    assert_eq!(todos.middlewares.as_deref(), Some("middlewares.ts"));
+    assert_eq!(child(todos, "[id]").relative_path, "app/todos/[id]");
+    let signin = child(child(&app_struct.app, "(auth)"), "signin");
+    assert_eq!(signin.route.as_deref(), Some("route.ts"));
+}
+
+#[test]
+fn scan_missing_src_dir() {
+    let fs = memory_fs(&[]);
+    assert!(scan(&fs, "src", "dist", &Convention::default()).is_err());
+}
+
+#[test]
+fn compile_reexported_handlers() {
+    let fs = memory_fs(&[
+        (
+            "src/handlers/todos.ts",
+            "export async function GET(req, res) { res.json([]); }",
+        ),
+        (
+            "src/app/todos/route.ts",
+            "export { GET } from \"../../handlers/todos\";",
+        ),
+    ]);
+    let convention = Convention::default();
+    let mut cache = ExportsCache::load(&fs, "dist", &convention);
+    let app_struct = compile(
+        &fs,
+        "src",
+        "dist",
+        "server.ts",
+        &convention,
+        &Config::default(),
+        &mut cache,
+    )
+    .unwrap();
+
+    let server = fs.read_to_string(Path::new("dist/server.ts")).unwrap();
+    assert!(server.contains("../src/app/todos/route"), "{}", server);
+    let manifest = build_manifest(&app_struct).unwrap();
+    assert_eq!(manifest.endpoints.len(), 1);
+    let handler = &manifest.endpoints[0].handlers[0];
+    assert_eq!(handler.export_name, "GET");
+    assert!(handler.is_async);
+    // The cache is saved next to the server, in memory as well
+    assert!(fs.exists(Path::new("dist/.nexp-cache/route-exports.json")));
+}
+
+#[test]
+fn render_codeframe_from_memory() {
+    let fs = memory_fs(&[("src/app/route.ts", "export const GET = (;")]);
+    let convention = Convention::default();
+    let mut app_struct = scan(&fs, "src", "dist", &convention).unwrap();
+    let error = analyze(
+        &fs,
+        &mut app_struct,
+        &convention,
+        &Config::default(),
+        &mut ExportsCache::disabled(),
+    )
+    .unwrap_err();
+
+    let rendered = Diagnostic::from_error(&error)[0].render(&fs);
+    assert!(rendered.contains("| export const GET = (;"), "{}", rendered);
+}
+
+#[test]
+fn analyze_returns_warnings() {
+    let route = (
+        "src/app/route.ts",
+        "export const GET = (req, res) => res.end();\nexport const post = (req, res) => res.end();",
+    );
+    let convention = Convention::default();
+    let analyze_files = |files: &[(&str, &str)]| {
+        let fs = memory_fs(files);
+        let mut app_struct = scan(&fs, "src", "dist", &convention).unwrap();
+        analyze(
+            &fs,
+            &mut app_struct,
+            &convention,
+            &Config::default(),
+            &mut ExportsCache::disabled(),
+        )
+        .map(|()| app_struct.warnings)
+    };
+
+    let warnings = analyze_files(&[route]).unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].severity, Severity::Warning);
+    assert!(warnings[0].message.contains("`post`"), "{}", warnings[0]);
+
+    // The warnings found before an error come along with it
+    let error =
+        analyze_files(&[route, ("src/app/broken/route.ts", "export const GET = (;")]).unwrap_err();
+    let severities = Diagnostic::from_error(&error)
+        .iter()
+        .map(|diagnostic| diagnostic.severity)
+        .collect::<Vec<_>>();
+    assert_eq!(severities, [Severity::Warning, Severity::Error]);
+}
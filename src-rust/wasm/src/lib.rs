@@ -0,0 +1,191 @@
+use anyhow::{Context, anyhow};
+use js_sys::{Error, JSON, Reflect};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+use nexp_compiler_rs::cache::ExportsCache;
+use nexp_compiler_rs::diagnostics::Diagnostic;
+use nexp_compiler_rs::manifest::{self, RouteManifest};
+use nexp_compiler_rs::project_config::ProjectConfig;
+use nexp_compiler_rs::vfs::{FileSystem, MemoryFs};
+use nexp_compiler_rs::{AppStruct, Config, Convention, ImportStyle, RouteStyle, routes};
+
+/// Options of every function, named and defaulting like the flags of the CLI.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+struct CompileOptions {
+    /// `src` by default
+    src_dir: Option<String>,
+    /// `nexp-compiled` by default
+    dist_dir: Option<String>,
+    /// Name of the generated server, `server.ts` by default
+    filename: Option<String>,
+    strict: Option<bool>,
+    route_style: Option<RouteStyle>,
+    import_style: Option<ImportStyle>,
+    /// Project config among the files, `nexp.config.json` next to the source directory if
+    /// it is one of them
+    config: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CompileOutput {
+    server: String,
+    manifest: RouteManifest,
+}
+
+/// The files and options of a call, resolved like the CLI does. Paths are relative to `/`.
+struct Compiler {
+    fs: MemoryFs,
+    src_dir: String,
+    dist_dir: String,
+    filename: String,
+    convention: Convention,
+    config: Config,
+}
+
+impl Compiler {
+    fn new(files: &JsValue, options: &JsValue) -> anyhow::Result<Self> {
+        let files: BTreeMap<String, String> =
+            from_js(files).context("`files` must map paths to source code")?;
+        let options: CompileOptions = if options.is_undefined() || options.is_null() {
+            CompileOptions::default()
+        } else {
+            from_js(options).context("Invalid options")?
+        };
+        let fs = MemoryFs::from_files(files);
+        let src_dir = options.src_dir.unwrap_or_else(|| "src".to_string());
+
+        let mut convention = Convention::default();
+        let mut config = Config::default();
+        ProjectConfig::load(&fs, options.config.as_deref(), &src_dir)?
+            .apply(&mut convention, &mut config);
+        // Options win over the project config
+        if let Some(route_style) = options.route_style {
+            config.route_style = route_style;
+        }
+        if let Some(import_style) = options.import_style {
+            config.import_style = import_style;
+        }
+        if options.strict == Some(true) {
+            config.strict = true;
+        }
+        convention.apply_import_style(config.import_style);
+
+        let dist_dir = options
+            .dist_dir
+            .unwrap_or_else(|| "nexp-compiled".to_string());
+        // The generated imports are relative to the dist directory, which holds no file yet
+        fs.create_dir_all(Path::new(&dist_dir))?;
+        Ok(Self {
+            fs,
+            src_dir,
+            dist_dir,
+            filename: options.filename.unwrap_or_else(|| "server.ts".to_string()),
+            convention,
+            config,
+        })
+    }
+
+    /// Scan and analyze the source directory without generating the server.
+    fn analyze(&self) -> anyhow::Result<AppStruct> {
+        let mut app_struct =
+            nexp_compiler_rs::scan(&self.fs, &self.src_dir, &self.dist_dir, &self.convention)?;
+        nexp_compiler_rs::analyze(
+            &self.fs,
+            &mut app_struct,
+            &self.convention,
+            &self.config,
+            &mut ExportsCache::disabled(),
+        )?;
+        Ok(app_struct)
+    }
+}
+
+fn from_js<T: DeserializeOwned>(value: &JsValue) -> anyhow::Result<T> {
+    let json = JSON::stringify(value)
+        .ok()
+        .and_then(|json| json.as_string())
+        .ok_or_else(|| anyhow!("Expected a JSON value"))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
+fn to_js(value: &impl Serialize) -> Result<JsValue, JsValue> {
+    let json = serde_json::to_string(value).map_err(|e| Error::new(&e.to_string()))?;
+    JSON::parse(&json)
+}
+
+/// Run `f` with the resolved files and options, turning a failure into a thrown `Error`
+/// whose `diagnostics` are the objects printed by `--message-format json`.
+fn with_compiler<T: Serialize>(
+    files: &JsValue,
+    options: &JsValue,
+    f: impl FnOnce(&Compiler) -> anyhow::Result<T>,
+) -> Result<JsValue, JsValue> {
+    // Codeframes are read from the files of the call, when they could be resolved
+    let (fs, result) = match Compiler::new(files, options) {
+        Ok(compiler) => {
+            let result = f(&compiler);
+            (compiler.fs, result)
+        }
+        Err(e) => (MemoryFs::new(), Err(e)),
+    };
+    let value = result.map_err(|e| {
+        let diagnostics = Diagnostic::from_error(&e);
+        let message = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.render(&fs))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let json = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.to_json(&fs))
+            .collect::<Vec<_>>();
+        let error = Error::new(&message);
+        if let Ok(json) = to_js(&json) {
+            let _ = Reflect::set(&error, &JsValue::from_str("diagnostics"), &json);
+        }
+        JsValue::from(error)
+    })?;
+    to_js(&value)
+}
+
+/// Compile the server of `files`, returning it along with the route manifest.
+#[wasm_bindgen]
+pub fn compile(files: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    with_compiler(&files, &options, |compiler| {
+        let app_struct = nexp_compiler_rs::compile(
+            &compiler.fs,
+            &compiler.src_dir,
+            &compiler.dist_dir,
+            &compiler.filename,
+            &compiler.convention,
+            &compiler.config,
+            &mut ExportsCache::disabled(),
+        )?;
+        let server_path = Path::new(&compiler.dist_dir).join(&compiler.filename);
+        Ok(CompileOutput {
+            server: compiler.fs.read_to_string(&server_path)?,
+            manifest: manifest::build_manifest(&app_struct)?,
+        })
+    })
+}
+
+/// The app directory tree, with the endpoint of every route.
+#[wasm_bindgen(js_name = getRoutes)]
+pub fn get_routes(files: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    with_compiler(&files, &options, |compiler| {
+        routes::route_tree(&compiler.analyze()?)
+    })
+}
+
+/// The route manifest, without generating the server.
+#[wasm_bindgen(js_name = getManifest)]
+pub fn get_manifest(files: JsValue, options: JsValue) -> Result<JsValue, JsValue> {
+    with_compiler(&files, &options, |compiler| {
+        manifest::build_manifest(&compiler.analyze()?)
+    })
+}
@@ -53,6 +53,38 @@ abstract class BaseCommand extends Command {
   port = Option.String("--port,-p", "3000", {
     description: "Port to run the server",
   });
+
+  manifest = Option.String("--manifest", {
+    description: "Write a JSON manifest of every route to this path",
+  });
+
+  openapi = Option.String("--openapi", {
+    description: "Write an OpenAPI 3.1 document of every route to this path",
+  });
+
+  strict = Option.Boolean("--strict", false, {
+    description: "Fail on route exports that look like misnamed HTTP methods",
+  });
+
+  routeStyle = Option.String("--route-style", {
+    description:
+      "Register handlers with a single `all` dispatcher (dispatch) or one route per method (native)",
+  });
+
+  importStyle = Option.String("--import-style", {
+    description:
+      "Import sources without extensions (bundler), with them (node-esm) or with require (commonjs)",
+  });
+
+  messageFormat = Option.String("--message-format", {
+    description:
+      "Print errors with a codeframe (human) or as one JSON object per line (json)",
+  });
+
+  config = Option.String("--config", {
+    description:
+      "Project config file overriding the conventions, nexp.config.json next to the source directory if it exists",
+  });
 }
 
 class DevCommand extends BaseCommand {
@@ -71,7 +103,15 @@ class DevCommand extends BaseCommand {
   });
 
   private beforeBuild() {
-    compileServer(this.srcDir, this.distDir, this.server);
+    compileServer(this.srcDir, this.distDir, this.server, {
+      manifest: this.manifest,
+      openapi: this.openapi,
+      strict: this.strict,
+      routeStyle: this.routeStyle,
+      importStyle: this.importStyle,
+      messageFormat: this.messageFormat,
+      config: this.config,
+    });
     generateEntryFile(this.port, this.server, this.entry, this.distDir);
   }
 
@@ -112,7 +152,15 @@ class CompileCommand extends BaseCommand {
   async execute() {
     const startTime = performance.now();
 
-    compileServer(this.srcDir, this.distDir, this.server);
+    compileServer(this.srcDir, this.distDir, this.server, {
+      manifest: this.manifest,
+      openapi: this.openapi,
+      strict: this.strict,
+      routeStyle: this.routeStyle,
+      importStyle: this.importStyle,
+      messageFormat: this.messageFormat,
+      config: this.config,
+    });
     generateEntryFile(this.port, this.server, this.entry, this.distDir);
 
     logger.info(`Compiling completed in ${performance.now() - startTime}ms.`);
@@ -132,7 +180,15 @@ class BuildCommand extends BaseCommand {
   async execute() {
     const startTime = performance.now();
 
-    compileServer(this.srcDir, this.distDir, this.server);
+    compileServer(this.srcDir, this.distDir, this.server, {
+      manifest: this.manifest,
+      openapi: this.openapi,
+      strict: this.strict,
+      routeStyle: this.routeStyle,
+      importStyle: this.importStyle,
+      messageFormat: this.messageFormat,
+      config: this.config,
+    });
     generateEntryFile(this.port, this.server, this.entry, this.distDir);
     await build(
       tsupConfig({
@@ -1,4 +1,12 @@
-import { existsSync, writeFileSync } from "fs";
+import {
+  existsSync,
+  mkdirSync,
+  readdirSync,
+  readFileSync,
+  statSync,
+  writeFileSync,
+} from "fs";
+import { createRequire } from "module";
 import path from "path";
 import { fileURLToPath } from "url";
 import { logger } from "./env-logger";
@@ -14,19 +22,41 @@ try {
   DIR_NAME = path.dirname(fileURLToPath(import.meta.url));
 }
 
-export function findCompilerExecutable() {
+// Name of the directories holding the pre-built compilers of the current platform
+function findTargetName() {
   const platform = process.platform;
   const arch = process.arch;
 
-  // Determine the binary directory based on the platform and architecture
-  let binaryDir: string | undefined;
   if (platform === "darwin") {
-    binaryDir = path.resolve(DIR_NAME, "macos-universal");
+    return "macos-universal";
   } else if (platform === "linux" && arch === "x64") {
-    binaryDir = path.resolve(DIR_NAME, "x86_64-unknown-linux-gnu");
+    return "x86_64-unknown-linux-gnu";
   } else if (platform === "win32" && arch === "x64") {
-    binaryDir = path.resolve(DIR_NAME, "x86_64-pc-windows-gnu");
+    return "x86_64-pc-windows-gnu";
   }
+  return undefined;
+}
+
+function findBinaryDir() {
+  const targetName = findTargetName();
+  return targetName && path.resolve(DIR_NAME, targetName);
+}
+
+// Root of the next-express package, which ships the `native` and `wasm` directories
+function findPackageRoot() {
+  let dir = DIR_NAME;
+  while (!existsSync(path.resolve(dir, "package.json"))) {
+    const parent = path.dirname(dir);
+    if (parent === dir) {
+      return DIR_NAME;
+    }
+    dir = parent;
+  }
+  return dir;
+}
+
+export function findCompilerExecutable() {
+  const binaryDir = findBinaryDir();
 
   // If has pre-built binary, use it. If not, fall back to TypeScript implementation
   let executablePath = path.resolve(DIR_NAME, "nexp-compiler-ts.js");
@@ -40,6 +70,286 @@ export function findCompilerExecutable() {
   return executablePath;
 }
 
+// Options of the native addon functions, named like the CLI flags
+export type CompilerAddonOptions = {
+  srcDir?: string;
+  distDir?: string;
+  filename?: string;
+  manifest?: string;
+  openapi?: string;
+  noCache?: boolean;
+  strict?: boolean;
+  routeStyle?: string;
+  importStyle?: string;
+  config?: string;
+};
+
+// Same shape as `--message-format json` output
+export type CompilerDiagnostic = {
+  severity: "error" | "warning";
+  code: string | null;
+  category: string | null;
+  message: string;
+  file: string | null;
+  line: number | null;
+  column: number | null;
+  notes: string[];
+  rendered: string;
+};
+
+export type ManifestEndpoint = {
+  url: string;
+  methods: string[];
+  handlers: { method: string; exportName: string; isAsync: boolean }[];
+  source: string;
+  middlewares: string[];
+};
+
+export type RouteManifest = {
+  version: number;
+  settings: string | null;
+  tailMiddlewares: string | null;
+  endpoints: ManifestEndpoint[];
+};
+
+export type RouteNode = {
+  name: string;
+  path: string;
+  middlewares: string | null;
+  endpoint: {
+    url: string;
+    methods: string[];
+    middlewares: string[];
+    source: string;
+  } | null;
+  children: RouteNode[];
+};
+
+// Results of the addon, along with the warnings found in the sources
+export type WithWarnings<T> = T & { warnings: CompilerDiagnostic[] };
+
+// Functions throw an `Error` with the `diagnostics` of the failure
+export type CompilerAddon = {
+  compile(options?: CompilerAddonOptions): WithWarnings<RouteManifest>;
+  getRoutes(options?: CompilerAddonOptions): WithWarnings<RouteNode>;
+  getManifest(options?: CompilerAddonOptions): WithWarnings<RouteManifest>;
+};
+
+export function isCompilerError(
+  error: unknown,
+): error is Error & { diagnostics: CompilerDiagnostic[] } {
+  return error instanceof Error && "diagnostics" in error;
+}
+
+// The native addon at `NEXP_COMPILER_ADDON`, or the one shipped for the current platform
+export function findCompilerAddon(): CompilerAddon | undefined {
+  const targetName = findTargetName();
+  const addonPath =
+    process.env.NEXP_COMPILER_ADDON ??
+    (targetName &&
+      path.resolve(
+        findPackageRoot(),
+        "native",
+        targetName,
+        "nexp-compiler.node",
+      ));
+  if (!addonPath || !existsSync(addonPath)) {
+    return undefined;
+  }
+  const requireAddon = createRequire(path.resolve(DIR_NAME, "build.js"));
+  return requireAddon(addonPath) as CompilerAddon;
+}
+
+// Options of the WebAssembly compiler, paths are keys of its `files`
+export type CompilerWasmOptions = Omit<
+  CompilerAddonOptions,
+  "manifest" | "openapi" | "noCache"
+>;
+
+// Compiles sources passed in memory, keyed by absolute POSIX paths
+export type CompilerWasm = {
+  compile(
+    files: Record<string, string>,
+    options?: CompilerWasmOptions,
+  ): { server: string; manifest: RouteManifest };
+  getRoutes(
+    files: Record<string, string>,
+    options?: CompilerWasmOptions,
+  ): RouteNode;
+  getManifest(
+    files: Record<string, string>,
+    options?: CompilerWasmOptions,
+  ): RouteManifest;
+};
+
+// The WebAssembly compiler, built by wasm-bindgen for Node.js into the `wasm` directory
+export function findCompilerWasm(): CompilerWasm | undefined {
+  const wasmPath = path.resolve(
+    findPackageRoot(),
+    "wasm",
+    "nexp_compiler_wasm.js",
+  );
+  if (!existsSync(wasmPath)) {
+    return undefined;
+  }
+  const requireWasm = createRequire(path.resolve(DIR_NAME, "build.js"));
+  return requireWasm(wasmPath) as CompilerWasm;
+}
+
+// Absolute POSIX path for the WebAssembly compiler, e.g. `/C:/app/src`
+function toWasmPath(filePath: string) {
+  const posixPath = path.resolve(filePath).split(path.sep).join("/");
+  return posixPath.startsWith("/") ? posixPath : `/${posixPath}`;
+}
+
+// Extensions the compiler resolves relative modules with, in the order it tries them
+const SOURCE_EXTENSIONS = [
+  ".ts",
+  ".js",
+  ".mts",
+  ".cts",
+  ".mjs",
+  ".cjs",
+  ".tsx",
+  ".jsx",
+];
+const RELATIVE_SPECIFIER = /\bfrom\s*["'](\.[^"']*)["']/g;
+
+function listFiles(dir: string, files: string[] = []) {
+  for (const entry of readdirSync(dir, { withFileTypes: true })) {
+    const entryPath = path.join(dir, entry.name);
+    if (entry.isDirectory()) {
+      listFiles(entryPath, files);
+    } else if (entry.isFile()) {
+      files.push(entryPath);
+    }
+  }
+  return files;
+}
+
+// Resolve a relative specifier like the compiler does: the exact path, the path with each
+// extension, `.js` specifiers pointing at `.ts` files, then directory index files
+function resolveModulePath(fromFile: string, specifier: string) {
+  const base = path.resolve(path.dirname(fromFile), specifier);
+  const candidates = [base, ...SOURCE_EXTENSIONS.map((ext) => base + ext)];
+  const baseExt = path.extname(base);
+  if (SOURCE_EXTENSIONS.includes(baseExt)) {
+    const stem = base.slice(0, -baseExt.length);
+    candidates.push(...SOURCE_EXTENSIONS.map((ext) => stem + ext));
+  }
+  candidates.push(
+    ...SOURCE_EXTENSIONS.map((ext) => path.join(base, `index${ext}`)),
+  );
+  return candidates.find(
+    (candidate) => existsSync(candidate) && statSync(candidate).isFile(),
+  );
+}
+
+// The files of `srcDir`, along with the modules they import or re-export from, which may be
+// outside of it, keyed by their path for the WebAssembly compiler
+function readSourceFiles(srcDir: string) {
+  const files: Record<string, string> = {};
+  const pending = listFiles(srcDir);
+  while (pending.length > 0) {
+    const filePath = pending.pop() as string;
+    const wasmPath = toWasmPath(filePath);
+    if (wasmPath in files) {
+      continue;
+    }
+    const source = readFileSync(filePath, "utf8");
+    files[wasmPath] = source;
+    if (!SOURCE_EXTENSIONS.includes(path.extname(filePath))) {
+      continue;
+    }
+    for (const [, specifier] of source.matchAll(RELATIVE_SPECIFIER)) {
+      const modulePath = resolveModulePath(filePath, specifier);
+      if (modulePath) {
+        pending.push(modulePath);
+      }
+    }
+  }
+  return files;
+}
+
+function reportCompilerWarnings(
+  warnings: CompilerDiagnostic[],
+  messageFormat?: string,
+) {
+  for (const warning of warnings) {
+    console.error(
+      messageFormat === "json" ? JSON.stringify(warning) : warning.rendered,
+    );
+  }
+}
+
+function reportCompilerError(error: unknown, messageFormat?: string) {
+  if (isCompilerError(error) && messageFormat === "json") {
+    for (const diagnostic of error.diagnostics) {
+      console.error(JSON.stringify(diagnostic));
+    }
+  } else {
+    console.error(error instanceof Error ? error.message : error);
+  }
+  logger.error(`Server compilation failed.`);
+  process.exitCode = 1;
+}
+
+function compileWithWasm(
+  wasm: CompilerWasm,
+  srcDir: string,
+  distDir: string,
+  serverFileName: string,
+  options: CompileServerOptions,
+) {
+  logger.info("Using compiler: WebAssembly");
+  if (options.openapi) {
+    logger.warn("--openapi is only supported by the native compiler.");
+  }
+  const files = readSourceFiles(srcDir);
+  const configPath =
+    options.config ?? path.join(path.dirname(srcDir), "nexp.config.json");
+  const hasConfig = existsSync(configPath);
+  if (hasConfig) {
+    files[toWasmPath(configPath)] = readFileSync(configPath, "utf8");
+  }
+
+  let output: ReturnType<CompilerWasm["compile"]>;
+  try {
+    output = wasm.compile(files, {
+      srcDir: toWasmPath(srcDir),
+      distDir: toWasmPath(distDir),
+      filename: serverFileName,
+      strict: options.strict,
+      routeStyle: options.routeStyle,
+      importStyle: options.importStyle,
+      config: hasConfig ? toWasmPath(configPath) : undefined,
+    });
+  } catch (error) {
+    reportCompilerError(error, options.messageFormat);
+    return;
+  }
+
+  // Leave the file untouched when nothing changed, so watchers do not rebuild
+  const serverPath = path.resolve(distDir, serverFileName);
+  mkdirSync(path.dirname(serverPath), { recursive: true });
+  const existing = existsSync(serverPath)
+    ? readFileSync(serverPath, "utf8")
+    : undefined;
+  if (existing !== output.server) {
+    writeFileSync(serverPath, output.server);
+  }
+  if (options.manifest) {
+    mkdirSync(path.dirname(path.resolve(options.manifest)), {
+      recursive: true,
+    });
+    writeFileSync(
+      options.manifest,
+      `${JSON.stringify(output.manifest, null, 2)}\n`,
+    );
+  }
+  logger.info(`Server file compiled successfully.`);
+}
+
 const ENTRY_TEMPLATE = `import { createServer } from "/* __nextExpress_serverFileName__ */";
 
 const server = createServer();
@@ -66,13 +376,61 @@ export function generateEntryFile(
   logger.info(`Entry file generated successfully.`);
 }
 
+export type CompileServerOptions = {
+  // Path of the JSON route manifest, only supported by the native compiler
+  manifest?: string;
+  // Path of the OpenAPI document, only supported by the native compiler
+  openapi?: string;
+  // Fail on exports that look like misnamed method handlers, only supported by the native compiler
+  strict?: boolean;
+  // `dispatch` (default) or `native` handler registration, only supported by the native compiler
+  routeStyle?: string;
+  // `bundler` (default), `node-esm` or `commonjs` imports, only supported by the native compiler
+  importStyle?: string;
+  // `human` (default) or `json` diagnostics, only supported by the native compiler
+  messageFormat?: string;
+  // Project config file, `nexp.config.json` next to the source directory if it exists
+  config?: string;
+};
+
 export function compileServer(
   srcDir: string,
   distDir: string,
   serverFileName: string,
+  options: CompileServerOptions = {},
 ) {
+  const addon = findCompilerAddon();
+  if (addon) {
+    logger.info("Using compiler: native addon");
+    try {
+      const { warnings } = addon.compile({
+        srcDir,
+        distDir,
+        filename: serverFileName,
+        manifest: options.manifest,
+        openapi: options.openapi,
+        strict: options.strict,
+        routeStyle: options.routeStyle,
+        importStyle: options.importStyle,
+        config: options.config,
+      });
+      reportCompilerWarnings(warnings, options.messageFormat);
+    } catch (error) {
+      reportCompilerError(error, options.messageFormat);
+      return;
+    }
+    logger.info(`Server file compiled successfully.`);
+    return;
+  }
+
   const compilerExe = findCompilerExecutable();
-  if (compilerExe.endsWith("js")) {
+  const isNative = !compilerExe.endsWith("js");
+  const wasm = isNative ? undefined : findCompilerWasm();
+  if (wasm) {
+    compileWithWasm(wasm, srcDir, distDir, serverFileName, options);
+    return;
+  }
+  if (!isNative) {
     logger.warn(
       "Can not find a native binary for the compiler." +
         " Fallback to TypeScript implementation." +
@@ -81,10 +439,39 @@ export function compileServer(
   }
   logger.info(`Using compiler: ${compilerExe}`);
 
-  spawnSync(
-    compilerExe,
-    ["--src-dir", srcDir, "--dist-dir", distDir, "--filename", serverFileName],
-    { stdio: "inherit" },
-  );
+  const args = [
+    "--src-dir",
+    srcDir,
+    "--dist-dir",
+    distDir,
+    "--filename",
+    serverFileName,
+  ];
+  if (options.config) {
+    args.push("--config", options.config);
+  }
+  const nativeOnlyArgs: [string, string | boolean | undefined][] = [
+    ["--manifest", options.manifest],
+    ["--openapi", options.openapi],
+    ["--strict", options.strict],
+    ["--route-style", options.routeStyle],
+    ["--import-style", options.importStyle],
+    ["--message-format", options.messageFormat],
+  ];
+  for (const [flag, value] of nativeOnlyArgs) {
+    if (!value) continue;
+    if (isNative) {
+      args.push(...(typeof value === "string" ? [flag, value] : [flag]));
+    } else {
+      logger.warn(`${flag} is only supported by the native compiler.`);
+    }
+  }
+
+  const result = spawnSync(compilerExe, args, { stdio: "inherit" });
+  if (result.status !== 0) {
+    logger.error(`Server compilation failed.`);
+    process.exitCode = result.status ?? 1;
+    return;
+  }
   logger.info(`Server file compiled successfully.`);
 }
@@ -3,7 +3,7 @@ import type { Request, Response, NextFunction } from "express";
 export type RouteHandler<
   REQ extends Request = Request,
   RES extends Response = Response,
-> = (req: REQ, res: RES) => void | Promise<void>;
+> = (req: REQ, res: RES, next: NextFunction) => void | Promise<void>;
 
 export type Middleware<
   REQ extends Request = Request,
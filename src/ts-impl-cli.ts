@@ -38,12 +38,17 @@ class BuildCommand extends Command {
     description: "Entry filename",
   });
 
+  config = Option.String("--config", {
+    description:
+      "Project config file overriding the conventions, nexp.config.json next to the source directory if it exists",
+  });
+
   async execute() {
     const startTime = performance.now();
     logger.info(
       `Compiling next-express from ${this.srcDir} to ${this.distDir}/${this.filename}`,
     );
-    await compile(this.srcDir, this.distDir, this.filename);
+    await compile(this.srcDir, this.distDir, this.filename, this.config);
     logger.info(
       `Compiling completed successfully in ${performance.now() - startTime}ms`,
     );
@@ -15,6 +15,7 @@ import {
   VariableDeclaration,
   Expression,
   ArrowFunction,
+  Node,
 } from "ts-morph";
 import { EnvLogger } from "./env-logger";
 
@@ -52,6 +53,14 @@ type AppRoute = {
   // runtime properties
   subRouter?: SubRouter;
   parent?: AppRoute; // to find nearest router
+  routeConfig?: RouteConfig;
+};
+// statically evaluated `config` export of a route file
+type RouteConfig = {
+  bodyLimit?: number | string;
+  timeoutMs?: number;
+  methods?: string[];
+  disabled?: boolean;
 };
 type AppStruct = {
   cwd: string;
@@ -70,7 +79,7 @@ let Convention = {
 
   // routes
   appDirName: "app",
-  supportExt: [".ts", ".js"],
+  supportExt: [".ts", ".js", ".mts", ".cts", ".mjs", ".cjs", ".tsx", ".jsx"],
   routeFileBasename: "route",
   middlewaresFileBasename: "middlewares",
   tailMiddlewaresFileBasename: "tail-middlewares",
@@ -81,32 +90,244 @@ let Convention = {
   settingsExportName: "settings",
   middlewaresExportName: "middlewares",
   tailMiddlewaresExportName: "middlewares",
+  // only read by the native compiler, which generates the OpenAPI document
+  openapiExportName: "openapi",
+  configExportName: "config",
 };
 
 // eslint-disable-next-line prefer-const
 let Config = {
   methodNotAllowedRes:
     "res.status(405).send(`Method ${req.method} Not Allowed`);",
+  // sent to OPTIONS requests of routes without an OPTIONS export, after the Allow header
+  optionsRes: "res.status(204).end();",
+  // sent to requests still unanswered after the `timeoutMs` of their route config
+  timeoutRes: 'res.status(503).send("Service Unavailable");',
 };
 
+const PROJECT_CONFIG_FILE_NAME = "nexp.config.json";
+
+/**
+ * Apply a project config file over `Convention` and `Config`,
+ * `nexp.config.json` of the project root, the parent of `srcDir`, is optional.
+ *
+ * @example
+ * // nexp.config.json
+ * { "convention": { "appDirName": "api", "routeFileBasename": "handler" } }
+ */
+function loadProjectConfig(srcDir: string, configPath?: string) {
+  const filePath =
+    configPath ?? path.join(path.dirname(srcDir), PROJECT_CONFIG_FILE_NAME);
+  if (!configPath && !fs.existsSync(filePath)) return;
+  const invalid = (key: string, reason: string) =>
+    new Error(`Invalid project config ${filePath} at \`${key}\`: ${reason}`);
+  let projectConfig: unknown;
+  try {
+    projectConfig = JSON.parse(fs.readFileSync(filePath, "utf-8"));
+  } catch (e) {
+    throw invalid(".", (e as Error).message);
+  }
+
+  // each check returns why a value is invalid
+  type Check = (value: unknown) => string | undefined;
+  const expectString: Check = (v) =>
+    typeof v === "string" ? undefined : "expected a string";
+  const fileName: Check = (v) =>
+    typeof v === "string" && v && !/[/\\]/.test(v)
+      ? undefined
+      : "must be a non-empty file or directory name";
+  const identifier: Check = (v) =>
+    typeof v === "string" && /^[A-Za-z_$][\w$]*$/.test(v)
+      ? undefined
+      : "must be a valid JavaScript identifier";
+  const response: Check = (v) =>
+    expectString(v) ??
+    ((v as string).trim() ? undefined : "must not be empty");
+  const schema: Record<string, Record<string, Check>> = {
+    convention: {
+      serverTemplate: (v) =>
+        expectString(v) ??
+        ((v as string).includes("/* __nextExpress_routes__ */")
+          ? undefined
+          : "must contain the `/* __nextExpress_routes__ */` placeholder"),
+      appDirName: fileName,
+      supportExt: (v) =>
+        Array.isArray(v) &&
+        v.length > 0 &&
+        v.every((ext) => typeof ext === "string" && /^\../.test(ext))
+          ? undefined
+          : "must list extensions starting with a dot, e.g. \".ts\"",
+      routeFileBasename: fileName,
+      middlewaresFileBasename: fileName,
+      tailMiddlewaresFileBasename: fileName,
+      settingsFileBasename: fileName,
+      customServerBasename: fileName,
+      settingsExportName: identifier,
+      middlewaresExportName: identifier,
+      tailMiddlewaresExportName: identifier,
+      openapiExportName: identifier,
+      configExportName: identifier,
+    },
+    config: {
+      routeStyle: (v) =>
+        v === "dispatch" || v === "native"
+          ? undefined
+          : "expected `dispatch` or `native`",
+      importStyle: (v) =>
+        v === "bundler" || v === "node-esm" || v === "commonjs"
+          ? undefined
+          : "expected `bundler`, `node-esm` or `commonjs`",
+      methodNotAllowedRes: response,
+      optionsRes: response,
+      timeoutRes: response,
+      strict: (v) =>
+        typeof v === "boolean" ? undefined : "expected a boolean",
+    },
+  };
+
+  const isObject = (v: unknown): v is Record<string, unknown> =>
+    typeof v === "object" && v !== null && !Array.isArray(v);
+  if (!isObject(projectConfig)) throw invalid(".", "expected an object");
+  for (const [section, values] of Object.entries(projectConfig)) {
+    const checks = schema[section];
+    if (!checks) throw invalid(section, `unknown field \`${section}\``);
+    if (!isObject(values)) throw invalid(section, "expected an object");
+    for (const [key, value] of Object.entries(values)) {
+      const check = checks[key];
+      if (!check) throw invalid(section, `unknown field \`${key}\``);
+      const reason = check(value);
+      if (reason) throw invalid(`${section}.${key}`, reason);
+    }
+  }
+
+  const { convention = {}, config = {} } = projectConfig as Record<
+    string,
+    Record<string, unknown>
+  >;
+  Object.assign(Convention, convention);
+  for (const [key, value] of Object.entries(config)) {
+    if (["routeStyle", "importStyle", "strict"].includes(key)) {
+      logger.warn(
+        `\`config.${key}\` is only supported by the native compiler.`,
+      );
+      continue;
+    }
+    Object.assign(Config, { [key]: value });
+  }
+  logger.info(`Loaded project config from ${filePath}`);
+}
+
 function getConventionFilenames(basename: string): string[] {
   return Convention.supportExt.map((ext) => `${basename}${ext}`);
 }
 
+// Import specifier of a source file as the emitted server sees it:
+// TypeScript and JSX extensions are dropped for the bundler to resolve,
+// `.mts` and `.cts` files are imported through their `.mjs` and `.cjs` output
+function moduleImportPath(filePath: string): string {
+  const ext = path.extname(filePath);
+  const stem = filePath.slice(0, -ext.length);
+  switch (ext) {
+    case ".ts":
+    case ".tsx":
+    case ".js":
+    case ".jsx":
+      return stem;
+    case ".mts":
+      return `${stem}.mjs`;
+    case ".cts":
+      return `${stem}.cjs`;
+    default:
+      return filePath;
+  }
+}
+
+type RouteSegment =
+  | { kind: "static"; name: string }
+  | { kind: "virtualGroup"; name: string }
+  | { kind: "dynamic"; name: string }
+  | { kind: "catchAll"; name: string }
+  | { kind: "optionalCatchAll"; name: string };
+
+function parseRouteSegment(segment: string): RouteSegment {
+  if (segment.startsWith("[[...") && segment.endsWith("]]")) {
+    return { kind: "optionalCatchAll", name: segment.slice(5, -2) };
+  }
+  if (segment.startsWith("[") && segment.endsWith("]")) {
+    const inner = segment.slice(1, -1);
+    if (inner.startsWith("...")) {
+      return { kind: "catchAll", name: inner.slice(3) };
+    }
+    return { kind: "dynamic", name: inner };
+  }
+  if (segment.startsWith("(") && segment.endsWith(")")) {
+    return { kind: "virtualGroup", name: segment };
+  }
+  return { kind: "static", name: segment };
+}
+
+// Static segments must be registered before dynamic ones, and dynamic ones
+// before catch-alls, otherwise Express matches the wider pattern first.
+function routeSegmentOrder(segment: RouteSegment): number {
+  switch (segment.kind) {
+    case "static":
+    case "virtualGroup":
+      return 0;
+    case "dynamic":
+      return 1;
+    case "catchAll":
+      return 2;
+    case "optionalCatchAll":
+      return 3;
+  }
+}
+
+function isCatchAllSegment(segment: RouteSegment): boolean {
+  return segment.kind === "catchAll" || segment.kind === "optionalCatchAll";
+}
+
+// Express 5 path syntax for this segment, including the leading slash.
+function routeSegmentToExpressPath(segment: RouteSegment): string {
+  switch (segment.kind) {
+    case "static":
+      return `/${segment.name}`;
+    case "virtualGroup":
+      return "";
+    case "dynamic":
+      return `/:${segment.name}`;
+    case "catchAll":
+      return `/*${segment.name}`;
+    case "optionalCatchAll":
+      return `{/*${segment.name}}`;
+  }
+}
+
+function routeSegmentDisplay(segment: RouteSegment): string {
+  switch (segment.kind) {
+    case "static":
+    case "virtualGroup":
+      return segment.name;
+    case "dynamic":
+      return `:${segment.name}`;
+    case "catchAll":
+      return `*${segment.name}`;
+    case "optionalCatchAll":
+      return `{*${segment.name}}`;
+  }
+}
+
 function routeNameToIdentifier(name: string): string {
-  name = name.trim().replaceAll("-", "_");
-  if (name.startsWith("(") && name.endsWith(")")) {
+  const segment = parseRouteSegment(name.trim());
+  if (segment.kind === "virtualGroup") {
     throw new Error("Virtual group should not be used as a route name");
   }
-  // Handle dynamic route parameters [param] -> param
-  if (name.startsWith("[") && name.endsWith("]")) {
-    name = name.slice(1, -1);
-  }
-  return name;
+  // Handle dynamic route parameters [param], [...param], [[...param]] -> param
+  return segment.name.replaceAll("-", "_");
 }
 
 function uniqueRouteHandlerAlias(appRoute: AppRoute) {
   return appRoute.relativePath
+    .replaceAll("...", "")
     .replaceAll("/", "_")
     .replaceAll(".", "_")
     .replaceAll("-", "_")
@@ -116,29 +337,152 @@ function uniqueRouteHandlerAlias(appRoute: AppRoute) {
     .replaceAll("]", "");
 }
 
-function getRouteDisplayName(name: string): string {
-  // Convert [param] to :param for display in comments
-  if (name.startsWith("[") && name.endsWith("]")) {
-    const paramName = name.slice(1, -1);
-    return `:${paramName}`;
+// Names declared by the default server template and by the generated code
+// around the route aliases
+const TEMPLATE_NAMES = [
+  "express",
+  "app",
+  "createServer",
+  "appSettings",
+  "setting",
+  "topLevelMiddlewares",
+  "tailMiddlewares",
+  "require",
+  "module",
+  "exports",
+];
+
+// Reserved words and restricted names that can not be declared by a module
+const RESERVED_WORDS = new Set([
+  "arguments",
+  "await",
+  "break",
+  "case",
+  "catch",
+  "class",
+  "const",
+  "continue",
+  "debugger",
+  "default",
+  "delete",
+  "do",
+  "else",
+  "enum",
+  "eval",
+  "export",
+  "extends",
+  "false",
+  "finally",
+  "for",
+  "function",
+  "if",
+  "implements",
+  "import",
+  "in",
+  "instanceof",
+  "interface",
+  "let",
+  "new",
+  "null",
+  "package",
+  "private",
+  "protected",
+  "public",
+  "return",
+  "static",
+  "super",
+  "switch",
+  "this",
+  "throw",
+  "true",
+  "try",
+  "typeof",
+  "var",
+  "void",
+  "while",
+  "with",
+  "yield",
+]);
+
+// Turn any string into a valid JavaScript identifier. Characters outside of
+// ASCII are spelled out as their code point, so that 用户 and 订单 stay distinct.
+function toIdentifier(raw: string): string {
+  let identifier = "";
+  for (const char of raw) {
+    const codePoint = char.codePointAt(0) ?? 0;
+    if (/[A-Za-z0-9_$]/.test(char)) {
+      identifier += char;
+    } else if (codePoint < 0x80) {
+      identifier += "_";
+    } else {
+      identifier += `u${codePoint.toString(16)}`;
+    }
+  }
+  if (identifier === "" || /^[0-9]/.test(identifier)) {
+    identifier = `_${identifier}`;
   }
-  return name;
+  if (RESERVED_WORDS.has(identifier)) {
+    identifier += "_";
+  }
+  return identifier;
+}
+
+// Declare a valid identifier derived from `raw`, suffixed with a counter when an
+// earlier declaration already took it, e.g. for the user-home and user_home
+// directories.
+function declareIdentifier(declared: Set<string>, raw: string): string {
+  const base = toIdentifier(raw);
+  let identifier = base;
+  for (let counter = 2; declared.has(identifier); counter++) {
+    identifier = `${base}_${counter}`;
+  }
+  declared.add(identifier);
+  if (identifier !== raw) {
+    logger.debug(`Declared identifier ${identifier} for ${raw}`);
+  }
+  return identifier;
+}
+
+function getRouteDisplayName(name: string): string {
+  // Convert [param] to :param, [...param] to *param for display in comments
+  return routeSegmentDisplay(parseRouteSegment(name));
 }
 
 function getRouteDisplayPath(relativePath: string): string {
   // Convert path segments [param] to :param for display in comments
   return relativePath
     .split("/")
-    .map((segment) => {
-      if (segment.startsWith("[") && segment.endsWith("]")) {
-        const paramName = segment.slice(1, -1);
-        return `:${paramName}`;
-      }
-      return segment;
-    })
+    .map((segment) => routeSegmentDisplay(parseRouteSegment(segment)))
     .join("/");
 }
 
+// Segments of a path relative to the source directory, without the leading app dir
+function appRelativeSegments(relativePath: string): string[] {
+  return relativePath
+    .split("/")
+    .slice(1)
+    .filter((segment) => segment);
+}
+
+// Convert a directory relative path to the path its sub router is mounted at,
+// e.g. "app/manage/[org]" -> "/manage/:org".
+function relDirToRouterPath(relativePath: string): string {
+  const routerPath = appRelativeSegments(relativePath)
+    .map((segment) => routeSegmentToExpressPath(parseRouteSegment(segment)))
+    .join("");
+  return routerPath || "/";
+}
+
+// A directory can only have one route file, e.g. not both route.ts and route.js
+function setRouteFile(appRoute: AppRoute, filename: string) {
+  if (appRoute.route) {
+    throw new Error(
+      `Conflicting route files ${appRoute.relativePath}/${appRoute.route} and ${appRoute.relativePath}/${filename}, a directory can only have one`,
+    );
+  }
+  appRoute.route = filename;
+}
+
 function findAppRouteRecursive(
   appRoutes: AppRoute[],
   relativePath: string,
@@ -167,8 +511,8 @@ function getAppStruct(srcDir: string, distDir: string): AppStruct {
     srcDir,
     distToSrcRelPath: path.relative(distPath, srcPath),
     app: {
-      name: "app",
-      relativePath: "app",
+      name: Convention.appDirName,
+      relativePath: Convention.appDirName,
       children: [],
     },
   };
@@ -255,14 +599,18 @@ function getAppStruct(srcDir: string, distDir: string): AppStruct {
         if (isDirectChildrenOfAppDir) {
           const route = routeNames.find((filename) => filename === entry.name);
           if (route) {
-            logger.info(`Found app-level route file: app/${entry.name}`);
-            appStruct.app.route = route;
+            logger.info(
+              `Found app-level route file: ${Convention.appDirName}/${entry.name}`,
+            );
+            setRouteFile(appStruct.app, route);
           }
           const middlewares = middlewareNames.find(
             (filename) => filename === entry.name,
           );
           if (middlewares) {
-            logger.info(`Found app-level middleware file: app/${entry.name}`);
+            logger.info(
+              `Found app-level middleware file: ${Convention.appDirName}/${entry.name}`,
+            );
             appStruct.app.middlewares = middlewares;
           }
         } else {
@@ -284,7 +632,7 @@ function getAppStruct(srcDir: string, distDir: string): AppStruct {
               logger.info(
                 `Found route file: ${appRoute.relativePath}/${entry.name}`,
               );
-              appRoute.route = route;
+              setRouteFile(appRoute, route);
             }
             const middlewares = middlewareNames.find(
               (filename) => filename === entry.name,
@@ -311,30 +659,178 @@ function getAppStruct(srcDir: string, distDir: string): AppStruct {
   return appStruct;
 }
 
+// Convert the path of a route file relative to the source directory to its endpoint,
+// e.g. "app/todos/[id]/route.ts" -> "/todos/:id/".
 function relPathToEndpoint(relPath: string): string {
-  if (!relPath.startsWith("app/") || !relPath.endsWith("route.ts")) {
+  // the first segment is the app dir and the last one the route file
+  const lastSlash = relPath.lastIndexOf("/");
+  if (lastSlash === -1) {
     throw new Error(`Invalid route path: ${relPath}`);
   }
-  const relPathWithoutApp = relPath.slice("app/".length);
-  const splited = relPathWithoutApp.split("/");
   let endpoint = "";
-  for (const segment of splited) {
-    if (segment.startsWith("(") && segment.endsWith(")")) {
+  let endsWithCatchAll = false;
+  for (const rawSegment of appRelativeSegments(relPath.slice(0, lastSlash))) {
+    const segment = parseRouteSegment(rawSegment);
+    if (segment.kind === "virtualGroup") {
       continue;
     }
-    if (segment === "route.ts") {
-      endpoint += "/";
-    } else if (segment.startsWith("[") && segment.endsWith("]")) {
-      // Convert [param] to :param for Express dynamic routes
-      const paramName = segment.slice(1, -1);
-      endpoint += `/:${paramName}`;
-    } else {
-      endpoint += `/${segment}`;
+    if (endsWithCatchAll) {
+      throw new Error(
+        `Catch-all segment must be the last segment of a route: ${relPath}`,
+      );
     }
+    endsWithCatchAll = isCatchAllSegment(segment);
+    // Convert [param] to :param, [...param] to *param and [[...param]] to {/*param}
+    endpoint += routeSegmentToExpressPath(segment);
+  }
+  // Catch-all patterns consume the rest of the path, a trailing slash
+  // would make the last matched segment mandatory
+  if (!endsWithCatchAll) {
+    endpoint += "/";
   }
   return endpoint;
 }
 
+// Segments of the URLs a route directory matches: virtual groups are dropped,
+// parameter names erased and static names lowercased, as Express routes are
+// case-insensitive.
+function endpointShape(relativePath: string): string[] {
+  return appRelativeSegments(relativePath)
+    .map(parseRouteSegment)
+    .filter((segment) => segment.kind !== "virtualGroup")
+    .map((segment) => {
+      switch (segment.kind) {
+        case "dynamic":
+          return ":";
+        case "catchAll":
+          return "*";
+        case "optionalCatchAll":
+          return "{*}";
+        default:
+          return segment.name.toLowerCase();
+      }
+    });
+}
+
+// Fail when several enabled routes match the same URLs, e.g. app/(a)/users and
+// app/(b)/users, or app/posts/[id] and app/posts/[slug]. Only the first route
+// registered would ever be reached.
+function checkRouteConflicts(app: AppRoute) {
+  const routesByShape = new Map<string, string[]>();
+  const add = (shape: string[], source: string) => {
+    const key = shape.join("/");
+    routesByShape.set(key, [...(routesByShape.get(key) ?? []), source]);
+  };
+  const traverse = (appRoute: AppRoute) => {
+    if (appRoute.route && !appRoute.routeConfig?.disabled) {
+      const source = `${appRoute.relativePath}/${appRoute.route}`;
+      const shape = endpointShape(appRoute.relativePath);
+      add(shape, source);
+      // An optional catch-all also matches the URL of its parent directory
+      if (shape.at(-1) === "{*}") {
+        add(shape.slice(0, -1), source);
+      }
+    }
+    for (const child of appRoute.children) {
+      traverse(child);
+    }
+  };
+  traverse(app);
+
+  const conflicts: string[][] = [];
+  for (const key of [...routesByShape.keys()].sort()) {
+    const sources = routesByShape.get(key) ?? [];
+    // Two optional catch-alls collide on both of their shapes, report them once
+    const reported = conflicts.some(
+      (group) => group.join("\n") === sources.join("\n"),
+    );
+    if (sources.length > 1 && !reported) {
+      conflicts.push(sources);
+    }
+  }
+  if (conflicts.length === 0) return;
+
+  const groups = conflicts.map((sources) =>
+    sources
+      .map((source) => `  ${relPathToEndpoint(source)} <- ${source}`)
+      .join("\n"),
+  );
+  throw new Error(
+    `Found ${conflicts.length} group(s) of conflicting routes, only the first route of each group is reachable:\n\n${groups.join("\n\n")}`,
+  );
+}
+
+// Methods Express can route (Node's `http.METHODS`), except `M-SEARCH`
+const HTTP_METHODS = [
+  "ACL",
+  "BIND",
+  "CHECKOUT",
+  "CONNECT",
+  "COPY",
+  "DELETE",
+  "GET",
+  "HEAD",
+  "LINK",
+  "LOCK",
+  "MERGE",
+  "MKACTIVITY",
+  "MKCALENDAR",
+  "MKCOL",
+  "MOVE",
+  "NOTIFY",
+  "OPTIONS",
+  "PATCH",
+  "POST",
+  "PROPFIND",
+  "PROPPATCH",
+  "PURGE",
+  "PUT",
+  "QUERY",
+  "REBIND",
+  "REPORT",
+  "SEARCH",
+  "SOURCE",
+  "SUBSCRIBE",
+  "TRACE",
+  "UNBIND",
+  "UNLINK",
+  "UNLOCK",
+  "UNSUBSCRIBE",
+];
+
+// Handles every method a route does not export a handler for
+const FALLBACK_EXPORT = "default";
+
+function isHttpMethodExport(exportName: string, absPath: string) {
+  if (exportName === FALLBACK_EXPORT) return true;
+  if (HTTP_METHODS.includes(exportName)) return true;
+  if (HTTP_METHODS.includes(exportName.toUpperCase())) {
+    logger.warn(
+      `\`${exportName}\` is not a handler, HTTP method exports must be uppercase (\`${exportName.toUpperCase()}\`) in ${absPath}`,
+    );
+  } else {
+    logger.debug(
+      `Skipping export ${exportName} of ${absPath}, not an HTTP method`,
+    );
+  }
+  return false;
+}
+
+// Methods listed in the Allow header of a route, including the automatic HEAD and OPTIONS
+function allowedMethods(handlers: { exportName: string }[]) {
+  const methods = handlers.map((handler) =>
+    handler.exportName === FALLBACK_EXPORT ? "*" : handler.exportName,
+  );
+  if (methods.includes("*")) return methods;
+  if (methods.includes("GET") && !methods.includes("HEAD")) {
+    methods.push("HEAD");
+  }
+  if (!methods.includes("OPTIONS")) {
+    methods.push("OPTIONS");
+  }
+  return methods;
+}
+
 function getEndpointHandlers(absPath: string) {
   logger.debug(`Parsing endpoint handlers from: ${absPath}`);
 
@@ -377,8 +873,18 @@ function getEndpointHandlers(absPath: string) {
   const sourceFile = proj.addSourceFileAtPath(absPath);
   const exports = sourceFile.getExportedDeclarations();
   const results: { exportName: string; isAsync: boolean }[] = [];
+  // `middlewares` for the whole route, `<METHOD>_middlewares` for a single method
+  const middlewares: string[] = [];
 
   for (const [key, declarations] of exports) {
+    if (
+      key === Convention.middlewaresExportName ||
+      key.endsWith(`_${Convention.middlewaresExportName}`)
+    ) {
+      middlewares.push(key);
+      continue;
+    }
+    if (!isHttpMethodExport(key, absPath)) continue;
     for (const dec of declarations) {
       if (isFunctionDec(dec)) {
         const isAsync = dec.isAsync();
@@ -391,6 +897,25 @@ function getEndpointHandlers(absPath: string) {
           results.push({ exportName: key, isAsync });
         }
       }
+      // `export default async (req, res) => {}`
+      if (
+        dec.isKind(ts.SyntaxKind.ArrowFunction) ||
+        dec.isKind(ts.SyntaxKind.FunctionExpression)
+      ) {
+        results.push({ exportName: key, isAsync: dec.isAsync() });
+      }
+      // CommonJS: `exports.GET = ...` and `module.exports = { GET: ... }`
+      const init = Node.isBinaryExpression(dec)
+        ? dec.getRight()
+        : Node.isPropertyAssignment(dec)
+          ? dec.getInitializer()
+          : undefined;
+      if (Node.isArrowFunction(init) || Node.isFunctionExpression(init)) {
+        results.push({ exportName: key, isAsync: init.isAsync() });
+      }
+      if (Node.isMethodDeclaration(dec)) {
+        results.push({ exportName: key, isAsync: dec.isAsync() });
+      }
     }
   }
 
@@ -399,7 +924,160 @@ function getEndpointHandlers(absPath: string) {
     logger.debug(`  - ${handler.exportName} (async: ${handler.isAsync})`);
   }
 
-  return results;
+  const routeMiddlewares = middlewares.filter((name) => {
+    const method = routeMiddlewaresMethod(name);
+    if (!method || results.some((handler) => handler.exportName === method)) {
+      return true;
+    }
+    logger.warn(
+      `\`${name}\` has no matching \`${method}\` handler export in ${absPath}`,
+    );
+    return false;
+  });
+
+  return { handlers: results, middlewares: routeMiddlewares };
+}
+
+// "POST_middlewares" -> "POST", undefined for the route-wide middlewares
+function routeMiddlewaresMethod(exportName: string) {
+  const suffix = `_${Convention.middlewaresExportName}`;
+  return exportName.endsWith(suffix)
+    ? exportName.slice(0, -suffix.length)
+    : undefined;
+}
+
+// Evaluate a literal expression into JSON without running any code,
+// undefined for anything dynamic
+function staticJsonValue(expr: Node): unknown {
+  if (
+    Node.isParenthesizedExpression(expr) ||
+    Node.isAsExpression(expr) ||
+    Node.isSatisfiesExpression(expr)
+  ) {
+    return staticJsonValue(expr.getExpression());
+  }
+  if (
+    Node.isStringLiteral(expr) ||
+    Node.isNoSubstitutionTemplateLiteral(expr)
+  ) {
+    return expr.getLiteralValue();
+  }
+  if (Node.isNumericLiteral(expr)) return expr.getLiteralValue();
+  if (Node.isTrueLiteral(expr)) return true;
+  if (Node.isFalseLiteral(expr)) return false;
+  if (Node.isNullLiteral(expr)) return null;
+  if (
+    Node.isPrefixUnaryExpression(expr) &&
+    expr.getOperatorToken() === ts.SyntaxKind.MinusToken
+  ) {
+    const operand = expr.getOperand();
+    return Node.isNumericLiteral(operand)
+      ? -operand.getLiteralValue()
+      : undefined;
+  }
+  if (Node.isArrayLiteralExpression(expr)) {
+    const values = expr.getElements().map(staticJsonValue);
+    return values.includes(undefined) ? undefined : values;
+  }
+  if (Node.isObjectLiteralExpression(expr)) {
+    const object: Record<string, unknown> = {};
+    for (const prop of expr.getProperties()) {
+      if (!Node.isPropertyAssignment(prop)) return undefined;
+      const nameNode = prop.getNameNode();
+      const key = Node.isStringLiteral(nameNode)
+        ? nameNode.getLiteralValue()
+        : nameNode.getText();
+      const value = staticJsonValue(prop.getInitializerOrThrow());
+      if (value === undefined) return undefined;
+      object[key] = value;
+    }
+    return object;
+  }
+  return undefined;
+}
+
+function getRouteConfig(absPath: string): RouteConfig {
+  const proj = new Project({
+    skipFileDependencyResolution: true,
+    skipLoadingLibFiles: true,
+    skipAddingFilesFromTsConfig: true,
+    compilerOptions: {
+      allowJs: true,
+    },
+  });
+  const sourceFile = proj.addSourceFileAtPath(absPath);
+  const dec = sourceFile
+    .getExportedDeclarations()
+    .get(Convention.configExportName)
+    ?.find((dec): dec is VariableDeclaration =>
+      dec.isKind(ts.SyntaxKind.VariableDeclaration),
+    );
+  const init = dec?.getInitializer();
+  if (!init) return {};
+  const value = staticJsonValue(init);
+  if (value === undefined) {
+    logger.warn(
+      `Ignoring \`${Convention.configExportName}\` export of ${absPath}, it must be a JSON-like literal`,
+    );
+    return {};
+  }
+
+  const invalid = (reason: string) =>
+    new Error(
+      `Invalid \`${Convention.configExportName}\` export in ${absPath}: ${reason}`,
+    );
+  if (typeof value !== "object" || value === null || Array.isArray(value)) {
+    throw invalid("expected an object");
+  }
+  const config = value as Record<string, unknown>;
+  const isCount = (v: unknown) => Number.isInteger(v) && (v as number) >= 0;
+  for (const [key, v] of Object.entries(config)) {
+    const isValid = {
+      bodyLimit: () => isCount(v) || typeof v === "string",
+      timeoutMs: () => isCount(v),
+      methods: () => Array.isArray(v) && v.every((m) => typeof m === "string"),
+      disabled: () => typeof v === "boolean",
+    }[key];
+    if (!isValid) throw invalid(`unknown field \`${key}\``);
+    if (!isValid()) throw invalid(`invalid type of \`${key}\``);
+  }
+  const routeConfig = config as RouteConfig;
+  const method = routeConfig.methods?.find((m) => !HTTP_METHODS.includes(m));
+  if (method) {
+    throw invalid(
+      `\`${method}\` in \`methods\` is not an uppercase HTTP method`,
+    );
+  }
+  if (routeConfig.timeoutMs === 0) {
+    throw invalid("`timeoutMs` must be greater than 0");
+  }
+  return routeConfig;
+}
+
+// Whether the exported handler of `method` is routed, the fallback always is
+function routesMethod(routeConfig: RouteConfig, method: string) {
+  return (
+    method === FALLBACK_EXPORT ||
+    !routeConfig.methods ||
+    routeConfig.methods.includes(method)
+  );
+}
+
+// Generated middlewares applying a route config, registered ahead of the top-level ones
+function routeConfigMiddlewares(routeConfig: RouteConfig) {
+  const middlewares: string[] = [];
+  if (routeConfig.timeoutMs !== undefined) {
+    middlewares.push(
+      `(req, res, next) => { const timer = setTimeout(() => { if (!res.headersSent) { ${Config.timeoutRes} } }, ${routeConfig.timeoutMs}); res.on("close", () => clearTimeout(timer)); next(); }`,
+    );
+  }
+  if (routeConfig.bodyLimit !== undefined) {
+    // a body parsed here is skipped by the parsers of the top-level middlewares
+    const limit = JSON.stringify(routeConfig.bodyLimit);
+    middlewares.push(`express.json({ limit: ${limit} })`);
+    middlewares.push(`express.urlencoded({ extended: true, limit: ${limit} })`);
+  }
+  return middlewares;
 }
 
 function compileRoute(
@@ -408,6 +1086,7 @@ function compileRoute(
   appRoute: AppRoute,
   srcDir: string,
   distToSrcRelPath: string,
+  declared: Set<string>,
   nearestSubRouter?: SubRouter,
 ) {
   logger.debug(`Compiling route: ${appRoute.name} at ${appRoute.relativePath}`);
@@ -418,18 +1097,28 @@ function compileRoute(
   if (appRoute.middlewares) {
     logger.debug(`Setting up middleware router for: ${appRoute.name}`);
     // Calculate the full path from app root by converting relativePath to endpoint
-    // e.g., "app/manage/admin" -> "/manage/admin"
-    let groupRoutePath = "/" + appRoute.relativePath.replace(/^app\/?/, "");
+    // e.g., "app/manage/admin" -> "/manage/admin", "app/org/[id]" -> "/org/:id"
+    let groupRoutePath = relDirToRouterPath(appRoute.relativePath);
     // If there's a parent sub-router, we need to make this path relative to it
     if (nearestSubRouter) {
       groupRoutePath = groupRoutePath.replace(nearestSubRouter.path, "");
     }
     const routeIdentifier = routeNameToIdentifier(appRoute.name);
-    const routeMiddlewaresAlias = `${routeIdentifier}Middlewares`;
-    imports += `import { ${Convention.middlewaresExportName} as ${routeMiddlewaresAlias} } from "${distToSrcRelPath}/${appRoute.relativePath}/${appRoute.middlewares.replace(".ts", "").replace(".js", "")}";\n`;
-    const groupRouterIdentifier = `${routeIdentifier}Router`;
+    const routeMiddlewaresAlias = declareIdentifier(
+      declared,
+      `${routeIdentifier}Middlewares`,
+    );
+    imports += `import { ${Convention.middlewaresExportName} as ${routeMiddlewaresAlias} } from "${distToSrcRelPath}/${appRoute.relativePath}/${moduleImportPath(appRoute.middlewares)}";\n`;
+    const groupRouterIdentifier = declareIdentifier(
+      declared,
+      `${routeIdentifier}Router`,
+    );
     // Store the full path from app root for child routes to use
-    const fullRouterPath = "/" + appRoute.relativePath.replace(/^app\/?/, "");
+    const fullRouterPath = relDirToRouterPath(appRoute.relativePath);
+    // Routers mounted below a dynamic path must see the parent's params
+    const routerOptions = /[:*]/.test(fullRouterPath)
+      ? "{ mergeParams: true }"
+      : "";
     appRoute.subRouter = {
       identifier: groupRouterIdentifier,
       path: fullRouterPath,
@@ -440,12 +1129,12 @@ function compileRoute(
       identifier: groupRouterIdentifier,
       path: fullRouterPath,
     };
-    routes += `const ${groupRouterIdentifier} = express.Router();\n`;
+    routes += `const ${groupRouterIdentifier} = express.Router(${routerOptions});\n`;
     routes += `${parentRouter}.use("${groupRoutePath}", ${groupRouterIdentifier});\n`;
     routes += `${groupRouterIdentifier}.use(...${routeMiddlewaresAlias});\n`;
   }
 
-  if (appRoute.route) {
+  if (appRoute.route && !appRoute.routeConfig?.disabled) {
     logger.debug(`Processing route handlers for: ${appRoute.name}`);
 
     let endpointUri = relPathToEndpoint(
@@ -463,15 +1152,77 @@ function compileRoute(
       appRoute.relativePath,
       appRoute.route,
     );
-    const handlers = getEndpointHandlers(routeAbsPath);
+    const endpointHandlers = getEndpointHandlers(routeAbsPath);
+    // handlers left out of the configured methods are not routed, nor are their middlewares
+    const routeConfig = appRoute.routeConfig ?? {};
+    const handlers = endpointHandlers.handlers.filter((handler) =>
+      routesMethod(routeConfig, handler.exportName),
+    );
+    const middlewares = endpointHandlers.middlewares.filter((name) => {
+      const method = routeMiddlewaresMethod(name);
+      return !method || routesMethod(routeConfig, method);
+    });
+    const router = nearestSubRouter ? nearestSubRouter.identifier : "app";
+    const routeImportPath = `${distToSrcRelPath}/${appRoute.relativePath}/${moduleImportPath(appRoute.route)}`;
+
+    // Middlewares exported by the route file only guard this endpoint,
+    // the route-wide ones run before the per-method ones
+    const methodMiddlewares = new Map<string, string>();
+    for (const name of middlewares) {
+      const middlewaresAlias = declareIdentifier(
+        declared,
+        `${uniqueRouteHandlerAlias(appRoute)}_${name}`,
+      );
+      imports += `import { ${name} as ${middlewaresAlias} } from "${routeImportPath}";\n`;
+      const method = routeMiddlewaresMethod(name);
+      if (method) {
+        methodMiddlewares.set(method, middlewaresAlias);
+      } else {
+        routes += `${router}.all("${endpointUri}", ...${middlewaresAlias});\n`;
+      }
+    }
+
     let endpointHandlerInner = "";
+    let fallbackHandler: string | undefined;
+    let getHandler: string | undefined;
     for (const handler of handlers) {
-      const handlerAlias = `${uniqueRouteHandlerAlias(appRoute)}_${handler.exportName}`;
-      imports += `import { ${handler.exportName} as ${handlerAlias} } from "${distToSrcRelPath}/${appRoute.relativePath}/${appRoute.route.replace(".ts", "").replace(".js", "")}";\n`;
-      endpointHandlerInner += `if (req.method === "${handler.exportName}") { ${handler.isAsync ? "await " : ""}${handlerAlias}(req, res); return; }\n`;
+      const handlerAlias = declareIdentifier(
+        declared,
+        `${uniqueRouteHandlerAlias(appRoute)}_${handler.exportName}`,
+      );
+      imports += `import { ${handler.exportName} as ${handlerAlias} } from "${routeImportPath}";\n`;
+      // await every handler, a sync one may still return a promise
+      const handlerCall = `await ${handlerAlias}(req, res, next);`;
+      if (handler.exportName === FALLBACK_EXPORT) {
+        fallbackHandler = handlerCall;
+        continue;
+      }
+      if (handler.exportName === "GET") {
+        getHandler = handlerCall;
+      }
+      // the dispatcher below is reached through `next()` once they pass
+      const middlewaresAlias = methodMiddlewares.get(handler.exportName);
+      if (middlewaresAlias) {
+        routes += `${router}.${handler.exportName.toLowerCase()}("${endpointUri}", ...${middlewaresAlias});\n`;
+      }
+      endpointHandlerInner += `if (req.method === "${handler.exportName}") { ${handlerCall} return; }\n`;
     }
-    const router = nearestSubRouter ? nearestSubRouter.identifier : "app";
-    const endpointHandler = `${router}.all("${endpointUri}", async (req, res) => { ${endpointHandlerInner} ${Config.methodNotAllowedRes}});\n`;
+    // The default export handles every method without its own handler,
+    // otherwise HEAD and OPTIONS are answered automatically
+    let unhandledMethodRes = fallbackHandler;
+    if (unhandledMethodRes === undefined) {
+      const allowHeader = `res.set("Allow", "${allowedMethods(handlers).join(", ")}");`;
+      const hasHandler = (method: string) =>
+        handlers.some((handler) => handler.exportName === method);
+      if (getHandler && !hasHandler("HEAD")) {
+        endpointHandlerInner += `if (req.method === "HEAD") { ${getHandler} return; }\n`;
+      }
+      if (!hasHandler("OPTIONS")) {
+        endpointHandlerInner += `if (req.method === "OPTIONS") { ${allowHeader} ${Config.optionsRes} return; }\n`;
+      }
+      unhandledMethodRes = `${allowHeader} ${Config.methodNotAllowedRes}`;
+    }
+    const endpointHandler = `${router}.all("${endpointUri}", ${endpointWrapper(`${endpointHandlerInner} ${unhandledMethodRes}`)});\n`;
     routes += endpointHandler;
   }
   routes += "\n";
@@ -482,10 +1233,19 @@ function compileRoute(
   };
 }
 
+// forward thrown and rejected errors of a generated route handler to the error middlewares
+function endpointWrapper(body: string) {
+  return `async (req, res, next) => { try { ${body} } catch (err) { next(err); } }`;
+}
+
 // for test case, sort app route to match rust-impl
+// static segments come first so that they are not shadowed by dynamic siblings
 function sortAppRoute(route: AppRoute) {
-  route.children.sort((a, b) =>
-    a.name.toLowerCase().localeCompare(b.name.toLowerCase()),
+  route.children.sort(
+    (a, b) =>
+      routeSegmentOrder(parseRouteSegment(a.name)) -
+        routeSegmentOrder(parseRouteSegment(b.name)) ||
+      a.name.toLowerCase().localeCompare(b.name.toLowerCase()),
   );
   if (route.children.length > 1) {
     route.children.forEach((child) => {
@@ -544,12 +1304,43 @@ function compileAppStruct(appStruct: AppStruct) {
    */
   if (appStruct.settings) {
     logger.debug(`Adding settings import from: ${appStruct.settings}`);
-    imports += `import { ${Convention.settingsExportName} as appSettings } from "${appStruct.distToSrcRelPath}/${appStruct.settings.replace(".ts", "").replace(".js", "")}";\n`;
+    imports += `import { ${Convention.settingsExportName} as appSettings } from "${appStruct.distToSrcRelPath}/${moduleImportPath(appStruct.settings)}";\n`;
     settings += `for (const setting of appSettings) {
       app.set(setting.name, setting.value);
     }\n`;
   }
 
+  /**
+   * Route configs apply before the top-level middlewares,
+   * so that e.g. a route body limit is not preempted by a global body parser
+   *
+   * @example
+   * // src/app/upload/route.ts
+   * export const config = { bodyLimit: "100mb", timeoutMs: 60_000 };
+   */
+  const compileRouteConfig = (appRoute: AppRoute) => {
+    if (appRoute.route) {
+      appRoute.routeConfig = getRouteConfig(
+        path.resolve(appStruct.srcDir, appRoute.relativePath, appRoute.route),
+      );
+      if (appRoute.routeConfig.disabled) {
+        logger.info(
+          `Skipping disabled route ${appRoute.relativePath}/${appRoute.route}`,
+        );
+      } else {
+        const middlewares = routeConfigMiddlewares(appRoute.routeConfig);
+        if (middlewares.length > 0) {
+          topLevelMiddlewares += `app.all("${relPathToEndpoint(`${appRoute.relativePath}/${appRoute.route}`)}", ${middlewares.join(", ")});\n`;
+        }
+      }
+    }
+    for (const child of appRoute.children) {
+      compileRouteConfig(child);
+    }
+  };
+  compileRouteConfig(appStruct.app);
+  checkRouteConflicts(appStruct.app);
+
   /**
    * Expecting an array of middlewares
    *
@@ -561,7 +1352,7 @@ function compileAppStruct(appStruct: AppStruct) {
     logger.debug(
       `Adding top-level middlewares import from: ${appStruct.topLevelMiddlewares}`,
     );
-    imports += `import { ${Convention.middlewaresExportName} as topLevelMiddlewares } from "${appStruct.distToSrcRelPath}/${appStruct.topLevelMiddlewares.replace(".ts", "").replace(".js", "")}";\n`;
+    imports += `import { ${Convention.middlewaresExportName} as topLevelMiddlewares } from "${appStruct.distToSrcRelPath}/${moduleImportPath(appStruct.topLevelMiddlewares)}";\n`;
     topLevelMiddlewares += `app.use(...topLevelMiddlewares);\n`;
   }
 
@@ -578,10 +1369,13 @@ function compileAppStruct(appStruct: AppStruct) {
     logger.debug(
       `Adding tail-middlewares handler import from: ${appStruct.tailMiddlewares}`,
     );
-    imports += `import { ${Convention.tailMiddlewaresExportName} as tailMiddlewares } from "${appStruct.distToSrcRelPath}/${appStruct.tailMiddlewares.replace(".ts", "").replace(".js", "")}";\n`;
+    imports += `import { ${Convention.tailMiddlewaresExportName} as tailMiddlewares } from "${appStruct.distToSrcRelPath}/${moduleImportPath(appStruct.tailMiddlewares)}";\n`;
     tailMiddlewares += `app.use(...tailMiddlewares);\n`;
   }
 
+  // Every alias of the routes gets its own identifier
+  const declared = new Set(TEMPLATE_NAMES);
+
   /**
    * 就像 Next.js 一样，期望 route[.ts,.js] 导出 GET、POST 等。
    *
@@ -594,7 +1388,10 @@ function compileAppStruct(appStruct: AppStruct) {
       `Traversing route: ${appRoute.name} (children: ${appRoute.children.length})`,
     );
 
-    if (appRoute.route || appRoute.middlewares) {
+    if (
+      (appRoute.route && !appRoute.routeConfig?.disabled) ||
+      appRoute.middlewares
+    ) {
       const nearestSubRouter = findNearestSubRouter(appRoute);
       const transformed = compileRoute(
         imports,
@@ -602,6 +1399,7 @@ function compileAppStruct(appStruct: AppStruct) {
         appRoute,
         appStruct.srcDir,
         appStruct.distToSrcRelPath,
+        declared,
         nearestSubRouter,
       );
       imports = transformed.imports;
@@ -630,12 +1428,15 @@ export async function compile(
   srcDir: string,
   distDir: string,
   filename: string,
+  configPath?: string,
 ) {
   logger.info("Starting compilation process");
   logger.debug(
     `Parameters: srcDir: ${srcDir}, distDir: ${distDir}, filename: ${filename}`,
   );
 
+  loadProjectConfig(srcDir, configPath);
+
   const customServerFilenames = getConventionFilenames(
     Convention.customServerBasename,
   );
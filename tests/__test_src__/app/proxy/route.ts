@@ -0,0 +1,13 @@
+import { RouteHandler } from "@qordli/next-express";
+
+export const GET: RouteHandler = async (req, res) => {
+  // Handle GET request
+  res.send("Hello from GET in /proxy");
+};
+
+// Handle every other method
+const proxy: RouteHandler = async (req, res) => {
+  res.send(`Hello from ${req.method} in /proxy`);
+};
+
+export default proxy;
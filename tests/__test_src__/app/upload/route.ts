@@ -0,0 +1,27 @@
+import express from "express";
+import { RouteHandler } from "@qordli/next-express";
+
+// Uploads are larger than the global JSON limit and may take a while
+export const config = {
+  bodyLimit: "100mb",
+  timeoutMs: 60_000,
+};
+
+// Run for every method of /upload
+export const middlewares = [express.raw({ limit: "10mb" })];
+
+// Only run before POST
+export const POST_middlewares = [
+  (req: express.Request, res: express.Response, next: express.NextFunction) => {
+    if (!req.headers["content-length"]) {
+      res.status(411).end();
+      return;
+    }
+    next();
+  },
+];
+
+export const POST: RouteHandler = async (req, res) => {
+  // Handle POST request
+  res.send(`Uploaded ${req.body.length} bytes to /upload`);
+};
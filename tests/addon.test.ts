@@ -0,0 +1,106 @@
+import { describe, it, expect } from "vitest";
+import prettier from "prettier";
+import path from "path";
+import os from "os";
+import { mkdirSync, mkdtempSync, readFileSync, writeFileSync } from "fs";
+import { findCompilerAddon, isCompilerError } from "../src/build";
+import { getSnapshotContent, snapshotsPaths } from "./utils/snapshots";
+
+// Built by `pnpm build`, or pointed at with `NEXP_COMPILER_ADDON`
+const addon = findCompilerAddon();
+
+const srcDir = path.resolve(process.cwd(), "tests", "__test_src__");
+const distDir = path.resolve(process.cwd(), "tests", "dist");
+
+describe.skipIf(!addon)("native addon", () => {
+  it("should compile to a valid express app", async () => {
+    const filename = "test-default-server-addon.ts";
+
+    const manifest = addon!.compile({ srcDir, distDir, filename });
+    const resultFileContent = readFileSync(
+      path.resolve(distDir, filename),
+      "utf-8",
+    );
+    const result = await prettier.format(resultFileContent, {
+      parser: "typescript",
+    });
+
+    expect(result).eq(getSnapshotContent(snapshotsPaths.default));
+    expect(manifest.endpoints.map((endpoint) => endpoint.url)).toContain(
+      "/todos/:id/",
+    );
+  });
+
+  it("should list the routes and the manifest without compiling", () => {
+    const options = { srcDir, distDir, noCache: true };
+
+    const routes = addon!.getRoutes(options);
+    expect(routes.name).eq("app");
+    expect(routes.children.map((child) => child.name)).toContain("todos");
+
+    const manifest = addon!.getManifest(options);
+    expect(manifest.version).eq(1);
+    expect(manifest.endpoints.map((endpoint) => endpoint.source)).toContain(
+      "app/health/route.ts",
+    );
+  });
+
+  it("should return the warnings along with the results", () => {
+    const projectDir = mkdtempSync(path.join(os.tmpdir(), "nexp-addon-"));
+    const routeDir = path.join(projectDir, "src", "app");
+    mkdirSync(routeDir, { recursive: true });
+    writeFileSync(
+      path.join(routeDir, "route.ts"),
+      [
+        "export const GET = (req, res) => res.end();",
+        "export const post = (req, res) => res.end();",
+      ].join("\n"),
+    );
+    const options = {
+      srcDir: path.join(projectDir, "src"),
+      distDir: path.join(projectDir, "dist"),
+      noCache: true,
+    };
+
+    const { warnings } = addon!.compile(options);
+    expect(warnings).toHaveLength(1);
+    expect(warnings[0]).toMatchObject({
+      severity: "warning",
+      code: "NEXP003",
+      category: "bad-export",
+      line: 2,
+    });
+    expect(warnings[0].rendered).toContain("export const post");
+    expect(addon!.getRoutes(options).warnings).toEqual(warnings);
+    expect(addon!.getManifest(options).warnings).toEqual(warnings);
+  });
+
+  it("should throw the diagnostics of a failure", () => {
+    const projectDir = mkdtempSync(path.join(os.tmpdir(), "nexp-addon-"));
+    const routeDir = path.join(projectDir, "src", "app");
+    mkdirSync(routeDir, { recursive: true });
+    writeFileSync(path.join(routeDir, "route.ts"), "export const GET = (;");
+
+    let error: unknown;
+    try {
+      addon!.compile({
+        srcDir: path.join(projectDir, "src"),
+        distDir: path.join(projectDir, "dist"),
+        noCache: true,
+      });
+    } catch (e) {
+      error = e;
+    }
+
+    expect(isCompilerError(error)).toBe(true);
+    if (isCompilerError(error)) {
+      expect(error.diagnostics[0]).toMatchObject({
+        severity: "error",
+        code: "NEXP001",
+        category: "parse-error",
+        line: 1,
+      });
+      expect(error.message).toContain("export const GET = (;");
+    }
+  });
+});
@@ -0,0 +1,86 @@
+import { describe, it, expect } from "vitest";
+import { findCompilerWasm, isCompilerError } from "../src/build";
+
+// Built by `pnpm build` into the `wasm` directory
+const wasm = findCompilerWasm();
+
+const options = { srcDir: "/project/src", distDir: "/project/dist" };
+
+// Sources keyed by absolute POSIX paths, the handlers live outside of the source directory
+const files: Record<string, string> = {
+  "/project/src/app/route.ts": "export const GET = (req, res) => res.end();",
+  "/project/src/app/users/[id]/route.ts":
+    'export { getUser as GET, deleteUser as DELETE } from "../../../../handlers/users";',
+  "/project/handlers/users.ts": [
+    "export async function getUser(req, res) { res.json({}); }",
+    "export const deleteUser = (req, res) => res.status(204).end();",
+  ].join("\n"),
+};
+
+describe.skipIf(!wasm)("WebAssembly compiler", () => {
+  it("should compile the server of in-memory files", () => {
+    const { server, manifest } = wasm!.compile(files, options);
+
+    expect(server).toContain(
+      'import { GET as app_users_id_GET } from "../src/app/users/[id]/route";',
+    );
+    expect(manifest.endpoints).toHaveLength(2);
+    expect(manifest.endpoints[1]).toMatchObject({
+      url: "/users/:id/",
+      handlers: [
+        { method: "GET", exportName: "GET", isAsync: true },
+        { method: "DELETE", exportName: "DELETE", isAsync: false },
+      ],
+    });
+  });
+
+  it("should list the routes and the manifest", () => {
+    const routes = wasm!.getRoutes(files, options);
+    expect(routes.name).eq("app");
+    expect(routes.children.map((child) => child.name)).toEqual(["users"]);
+
+    const manifest = wasm!.getManifest(files, options);
+    expect(manifest.endpoints.map((endpoint) => endpoint.source)).toEqual([
+      "app/route.ts",
+      "app/users/[id]/route.ts",
+    ]);
+  });
+
+  it("should apply the project config next to the source directory", () => {
+    const { server } = wasm!.compile(
+      {
+        ...files,
+        "/project/nexp.config.json": JSON.stringify({
+          config: { importStyle: "node-esm" },
+        }),
+      },
+      options,
+    );
+
+    expect(server).toContain('from "../src/app/route.ts";');
+  });
+
+  it("should throw the diagnostics of a failure", () => {
+    let error: unknown;
+    try {
+      wasm!.compile(
+        { "/project/src/app/route.ts": "export const GET = (;" },
+        options,
+      );
+    } catch (e) {
+      error = e;
+    }
+
+    expect(isCompilerError(error)).toBe(true);
+    if (isCompilerError(error)) {
+      expect(error.diagnostics[0]).toMatchObject({
+        severity: "error",
+        code: "NEXP001",
+        file: "/project/src/app/route.ts",
+        line: 1,
+      });
+      // The codeframe is read from the in-memory files
+      expect(error.message).toContain("export const GET = (;");
+    }
+  });
+});